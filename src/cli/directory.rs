@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Subcommand, Debug)]
 pub enum DirectoryCommands {
@@ -9,17 +10,36 @@ pub enum DirectoryCommands {
         minimal: bool,
     },
     Start(StartDirectoryArgs),
+    /// Add a directory to the directories file
+    Add {
+        /// The directory, defaults to the current directory
+        path: Option<PathBuf>,
+        /// The name of the directory, defaults to the name of the directory itself
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// Remove a directory from the directories file
+    #[command(alias = "rm")]
+    Remove {
+        /// The name of the directory as it's defined in the config
+        name: String,
+    },
 }
 
 #[derive(Parser, Debug)]
 pub struct StartDirectoryArgs {
-    /// The directory to start the session in
+    /// The directory to start the session in, a `.moxide.yaml` in it is used as the project
+    #[arg(default_value = ".")]
     pub directory: String,
 
     /// Start the session detached
     #[arg(short, long, default_value_t = false)]
     pub detached: bool,
 
+    /// Attach to the session, overrides `detached` of the config
+    #[arg(short, long, default_value_t = false, conflicts_with = "detached")]
+    pub attach: bool,
+
     /// Specify the name of the tmux session
     ///
     /// Optionally provide a name for the session. If not provided, it will be either the name from the configuration or from the directory
@@ -29,4 +49,8 @@ pub struct StartDirectoryArgs {
     /// Always start a new session instead of attaching to an existing session
     #[arg(long, default_value_t = false)]
     pub always_new_session: bool,
+
+    /// Attach to an existing session, overrides `always_new_session` of the config
+    #[arg(long, default_value_t = false, conflicts_with = "always_new_session")]
+    pub reuse_session: bool,
 }
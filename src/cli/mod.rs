@@ -1,13 +1,24 @@
 pub mod directory;
 pub mod list;
 pub mod project;
+pub mod schema;
 pub mod template;
 
+use crate::exit::EXIT_CODES_HELP;
 use clap::{Parser, Subcommand};
+use moxide::formats::Format;
+use std::path::PathBuf;
+
+/// Parses a `key=value` pair of a template variable
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid variable `{arg}`, expected KEY=VALUE"))
+}
 
 /// A CLI for tmux session management
 #[derive(Parser, Debug)]
-#[clap(version)]
+#[clap(version, after_help = EXIT_CODES_HELP)]
 pub struct Cli {
     #[command(subcommand)]
     pub cmd: Commands,
@@ -43,9 +54,49 @@ pub enum Commands {
         #[command(subcommand)]
         action: project::ProjectCommands,
     },
+    /// Start a session in a directory, same as `dir start`
+    ///
+    /// A `.moxide.yaml` or `.moxide/project.yaml` in the directory is used as the project.
+    Start(directory::StartDirectoryArgs),
+    /// Trust the repository-local project file of a directory, so it can run commands
+    Trust {
+        /// The directory of the project file or the file itself
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Stop a session, the stop keys of its panes are sent before it's killed
+    Stop {
+        /// The name of a project, template, directory or session
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+        /// Stop every session that was started by moxide
+        #[arg(long, default_value_t = false, conflicts_with = "name")]
+        all: bool,
+        /// How many seconds to wait for the panes to exit before the session is killed
+        #[arg(long, default_value_t = 10)]
+        timeout: u64,
+    },
+    /// Rebuild the session of a project, template or directory from its current definition
+    Restart {
+        /// The name of the project, template or directory
+        name: String,
+        /// Don't attach to the new session
+        #[arg(short, long, default_value_t = false)]
+        detached: bool,
+        /// Attach to the new session, overrides `detached` of the config
+        #[arg(short, long, default_value_t = false, conflicts_with = "detached")]
+        attach: bool,
+    },
+    /// Show the running sessions and the project, template or directory they were started from
+    Status,
     /// List all moxide directories, templates and projecets
     #[command(alias = "ls")]
     List(list::ListCli),
+    /// Validate all configuration files
+    ///
+    /// Reports files that can't be parsed, missing templates and directories, duplicate names and
+    /// invalid layouts. Exits with a non-zero code if a problem was found.
+    Check,
     /// Save the current session into a new template
     #[command(alias = "fre", alias = "save")]
     Freeze {
@@ -58,8 +109,18 @@ pub enum Commands {
         /// Force overwrite existing files
         #[arg(short, long, default_value_t = false, group = "file")]
         force: bool,
+        /// The format of the project, defaults to the extension of the file name or yaml
+        #[arg(long, value_enum)]
+        format: Option<Format>,
         /// Use stdout instead of a file
         #[arg(long, default_value_t = false, conflicts_with = "file")]
         stdout: bool,
     },
+    /// Print the JSON Schema of a configuration file
+    ///
+    /// Editors with a yaml language server can use it to complete and validate the configuration.
+    Schema {
+        #[arg(value_enum)]
+        kind: schema::SchemaKind,
+    },
 }
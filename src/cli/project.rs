@@ -1,4 +1,6 @@
 use clap::{Parser, Subcommand};
+use moxide::formats::Format;
+use std::path::PathBuf;
 
 #[derive(Subcommand, Debug)]
 pub enum ProjectCommands {
@@ -11,6 +13,13 @@ pub enum ProjectCommands {
     },
     /// Start a specific project
     Start(ProjectStartArgs),
+    /// Create a new project file
+    New(ProjectNewArgs),
+    /// Open the file of a project in `$VISUAL` or `$EDITOR`
+    Edit {
+        /// The name of the project as it's defined in the config
+        name: String,
+    },
 }
 
 #[derive(Debug, Parser)]
@@ -22,7 +31,47 @@ pub struct ProjectStartArgs {
     #[arg(short, long, default_value_t = false)]
     pub detached: bool,
 
+    /// Attach to the session, overrides `detached` of the config
+    #[arg(short, long, default_value_t = false, conflicts_with = "detached")]
+    pub attach: bool,
+
     /// Always start a new session instead of attaching to an existing session
     #[arg(short = 'n', long, default_value_t = false)]
     pub always_new_session: bool,
+
+    /// Attach to an existing session, overrides `always_new_session` of the config
+    #[arg(long, default_value_t = false, conflicts_with = "always_new_session")]
+    pub reuse_session: bool,
+
+    /// Set a template variable, can be used multiple times
+    ///
+    /// Values from the command line take precedence over the `vars` of the project
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = super::parse_var)]
+    pub vars: Vec<(String, String)>,
+}
+
+#[derive(Debug, Parser)]
+pub struct ProjectNewArgs {
+    /// The name of the new project
+    pub name: String,
+
+    /// The root directory of the project, defaults to the current directory
+    #[arg(long, alias = "directory")]
+    pub dir: Option<PathBuf>,
+
+    /// The template the project uses, without one the project gets a single empty window
+    #[arg(short, long)]
+    pub template: Option<String>,
+
+    /// Use the windows of the current session instead of a template
+    #[arg(long, default_value_t = false, conflicts_with = "template")]
+    pub from_session: bool,
+
+    /// Force overwrite existing files
+    #[arg(short, long, default_value_t = false)]
+    pub force: bool,
+
+    /// The format of the project file
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
 }
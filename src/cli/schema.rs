@@ -0,0 +1,13 @@
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum SchemaKind {
+    /// A file in the projects directory
+    Project,
+    /// A file in the templates directory
+    Template,
+    /// The directories.yaml file
+    Directories,
+    /// The config.yaml file
+    Config,
+}
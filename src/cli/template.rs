@@ -12,6 +12,11 @@ pub enum TemplateCommands {
         all: bool,
     },
     Start(StartTemplateArgs),
+    /// Open the file of a template in `$VISUAL` or `$EDITOR`
+    Edit {
+        /// The name of the template as it's defined in the config
+        name: String,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -22,6 +27,10 @@ pub struct StartTemplateArgs {
     #[arg(short, long, default_value_t = false)]
     pub detached: bool,
 
+    /// Attach to the session, overrides `detached` of the config
+    #[arg(short, long, default_value_t = false, conflicts_with = "detached")]
+    pub attach: bool,
+
     /// The directory to start it in
     #[arg(long, alias = "dir")]
     pub directory: Option<String>,
@@ -35,4 +44,12 @@ pub struct StartTemplateArgs {
     /// Always start a new session instead of attaching to an existing session
     #[arg(long, default_value_t = false)]
     pub always_new_session: bool,
+
+    /// Attach to an existing session, overrides `always_new_session` of the config
+    #[arg(long, default_value_t = false, conflicts_with = "always_new_session")]
+    pub reuse_session: bool,
+
+    /// Set a template variable, can be used multiple times
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = super::parse_var)]
+    pub vars: Vec<(String, String)>,
 }
@@ -0,0 +1,216 @@
+use crate::{
+    exit,
+    exit::{ExitCode, ExitErr},
+};
+use colored::Colorize;
+use itertools::Itertools;
+use moxide::{
+    directories::parse_directories,
+    formats::{config_files, read_config_file},
+    helpers::{absolute_path, get_config_dir},
+    projects::{Project, ProjectSetup},
+    settings::Settings,
+    templates::{find_template, find_template_path, is_valid_layout, Template, Window},
+    MoxideError,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+struct Problem {
+    path: PathBuf,
+    message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            self.path.display().to_string().bold(),
+            self.message
+        )
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    problems: Vec<Problem>,
+}
+
+impl Checker {
+    fn report<M: fmt::Display>(&mut self, path: &Path, message: M) {
+        self.problems.push(Problem {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        });
+    }
+
+    /// Parses every file of a config directory, files that can't be parsed are reported
+    fn parse_dir<T: DeserializeOwned>(&mut self, dir: &Path) -> Vec<(PathBuf, T)> {
+        let paths = match config_files(dir) {
+            Ok(paths) => paths,
+            Err(err) => {
+                self.report(dir, err);
+                return Vec::new();
+            }
+        };
+
+        paths
+            .into_iter()
+            .filter_map(|path| match read_config_file(&path) {
+                Ok(parsed) => Some((path, parsed)),
+                Err(MoxideError::Parse { source, .. }) => {
+                    self.report(&path, source);
+                    None
+                }
+                Err(MoxideError::UnsupportedFormat(_)) => {
+                    self.report(
+                        &path,
+                        "Unsupported file extension, use yaml, yml, toml or json",
+                    );
+                    None
+                }
+                Err(err) => {
+                    self.report(&path, err);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Reports every name that is used by more than one file
+    fn check_duplicates<'a>(
+        &mut self,
+        kind: &str,
+        names: impl Iterator<Item = (&'a Path, &'a str)>,
+    ) {
+        let mut seen: HashMap<&str, &Path> = HashMap::new();
+        for (path, name) in names {
+            match seen.get(name) {
+                Some(first) => self.report(
+                    path,
+                    format!(
+                        "The {kind} name {name} is already used by {}",
+                        first.display()
+                    ),
+                ),
+                None => {
+                    seen.insert(name, path);
+                }
+            }
+        }
+    }
+
+    fn check_windows(&mut self, path: &Path, windows: &[Window]) {
+        for window in windows {
+            let Some(layout) = window.layout.as_deref() else {
+                continue;
+            };
+            // Layouts with variables are only known once the session is started
+            if !layout.contains("{{") && !is_valid_layout(layout) {
+                let name = window.name.as_deref().unwrap_or("without a name");
+                self.report(
+                    path,
+                    format!("The layout {layout} of window {name} is invalid"),
+                );
+            }
+        }
+    }
+
+    fn check_root_dir(&mut self, path: &Path, name: &str, dir: &Path) {
+        if absolute_path(dir).is_err() {
+            self.report(
+                path,
+                format!("The directory {} of {name} doesn't exist", dir.display()),
+            );
+        }
+    }
+
+    /// Templates are resolved the same way as when a session is started
+    fn check_template_ref(&mut self, path: &Path, template: &str) {
+        if let Err(err) = find_template_path(template) {
+            self.report(path, err);
+        }
+    }
+}
+
+pub fn check_handler() {
+    let config_dir = get_config_dir().exit_err();
+    let mut checker = Checker::default();
+
+    let templates: Vec<(PathBuf, Template)> = checker.parse_dir(&config_dir.join("templates/"));
+    checker.check_duplicates(
+        "template",
+        templates
+            .iter()
+            .map(|(path, template)| (path.as_path(), template.name.as_str())),
+    );
+    for (path, template) in &templates {
+        if template.extends.is_some() {
+            if let Err(err) = find_template(&template.name) {
+                checker.report(path, err);
+            }
+        }
+        checker.check_windows(path, &template.windows);
+    }
+
+    let projects: Vec<(PathBuf, Project)> = checker.parse_dir(&config_dir.join("projects/"));
+    checker.check_duplicates(
+        "project",
+        projects
+            .iter()
+            .map(|(path, project)| (path.as_path(), project.name.as_str())),
+    );
+    for (path, project) in &projects {
+        checker.check_root_dir(path, &project.name, &project.root_dir);
+        match &project.setup {
+            ProjectSetup::Template(template) => {
+                checker.check_template_ref(path, template);
+            }
+            ProjectSetup::Windows { windows } => checker.check_windows(path, windows),
+        }
+    }
+
+    let dirs_path = config_dir.join("directories.yaml");
+    match fs::read_to_string(&dirs_path).map(|content| parse_directories(&content)) {
+        Ok(Ok(dirs)) => {
+            for (name, dir) in dirs.into_iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
+                checker.check_root_dir(&dirs_path, &name, &dir.path);
+                if let Some(template) = &dir.template {
+                    checker.check_template_ref(&dirs_path, template);
+                }
+            }
+        }
+        Ok(Err(err)) => checker.report(&dirs_path, err),
+        Err(err) => checker.report(&dirs_path, format!("Can't read the file: {err}")),
+    }
+
+    let settings_path = Settings::path().exit_err();
+    match Settings::load() {
+        Ok(settings) => {
+            if let Some(template) = &settings.default_template {
+                checker.check_template_ref(&settings_path, template);
+            }
+        }
+        Err(MoxideError::Parse { source, .. }) => checker.report(&settings_path, source),
+        Err(err) => checker.report(&settings_path, err),
+    }
+
+    if checker.problems.is_empty() {
+        println!("{}", "All configuration files are valid".green());
+        return;
+    }
+
+    for problem in &checker.problems {
+        println!("{problem}");
+    }
+    exit!(
+        ExitCode::InvalidConfig,
+        "\n{} problems found",
+        checker.problems.len()
+    );
+}
@@ -1,23 +1,39 @@
 use crate::{
     cli::directory::{DirectoryCommands, StartDirectoryArgs},
-    directories::{parse_directory_config, Directories},
-    helpers::{absolute_path, dir_name, Exit, ExitErr},
-    tmux::{attach, session_exists},
-    widgets::table::Table,
+    commands::project::{start_project, start_session},
+    exit::{Exit, ExitCode, ExitErr},
+    prompt::prompt_variable,
 };
 use itertools::Itertools;
-use std::path::PathBuf;
-use tmux_interface::{NewSession, Tmux};
+use moxide::{
+    directories::{
+        add_directory, directories_path, directory_name, directory_session, parse_directory_config,
+        remove_directory, Directories,
+    },
+    error::MoxideError,
+    helpers::{absolute_path, dir_name},
+    matching::{not_found, resolve_name},
+    projects::{find_local_project_file, read_local_project},
+    settings::{resolve_flag, Settings},
+    trust,
+    widgets::table::Table,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 pub fn directory_handler(action: DirectoryCommands) {
     match action {
         DirectoryCommands::List { minimal } => list_handler(minimal),
         DirectoryCommands::Start(args) => start_handler(&args),
+        DirectoryCommands::Add { path, name } => add_handler(path, name),
+        DirectoryCommands::Remove { name } => remove_handler(&name),
     }
 }
 
 fn list_handler(minimal: bool) {
-    let dirs = parse_directory_config().exit_err(1);
+    let dirs = parse_directory_config().exit_err();
 
     if minimal {
         println!("{}", format_dirs_minimal(dirs));
@@ -25,54 +41,149 @@ fn list_handler(minimal: bool) {
     }
 
     let table: Table<_, _> = dirs.into();
-    println!("{table}");
+    let table_style = Settings::load().exit_err().table_style;
+    println!("{}", table.with_style(table_style));
+}
+
+fn add_handler(path: Option<PathBuf>, name: Option<String>) {
+    let path = path.unwrap_or_else(|| PathBuf::from("."));
+    let path = absolute_path(&path).exit(
+        ExitCode::NotFound,
+        format!("The directory {} doesn't exist", path.display()),
+    );
+    let name = name
+        .map_or_else(|| directory_name(&path), Ok)
+        .map_err(MoxideError::from)
+        .exit_err();
+
+    // A missing file is created with the first directory
+    let config = directories_path().exit_err();
+    let content = if config.exists() {
+        fs::read_to_string(&config)
+            .map_err(MoxideError::io(&config))
+            .exit_err()
+    } else {
+        String::new()
+    };
+
+    let content = add_directory(&content, &name, &path)
+        .map_err(MoxideError::from)
+        .exit_err();
+    fs::write(&config, content)
+        .map_err(MoxideError::io(&config))
+        .exit_err();
+    println!("Added {name} ({})", path.display());
+}
+
+fn remove_handler(name: &str) {
+    let config = directories_path().exit_err();
+    let content = fs::read_to_string(&config)
+        .map_err(MoxideError::io(&config))
+        .exit_err();
+
+    let content = remove_directory(&content, name)
+        .map_err(MoxideError::from)
+        .and_then(|content| content.ok_or_else(|| not_found("Directory", name)))
+        .exit_err();
+    fs::write(&config, content)
+        .map_err(MoxideError::io(&config))
+        .exit_err();
+    println!("Removed {name}");
 }
 
 fn format_dirs_minimal(dirs: Directories) -> String {
     dirs.into_iter()
-        .map(|(name, path)| format!("\"{}\" {}", name, path.display()))
+        .map(|(name, dir)| format!("\"{}\" {}", name, dir.path.display()))
         .join("\n")
 }
 
-fn start_handler(args: &StartDirectoryArgs) {
-    let (name, path) = resolve_dir_path(args);
-    let exists = session_exists(&name).unwrap_or(false);
-
-    let mut tmux = Tmux::new();
-    if args.always_new_session || !exists {
-        let cmd = NewSession::new()
-            .start_directory(path.to_string_lossy())
-            .detached()
-            .session_name(&name)
-            .window_name(&name);
-        tmux = tmux.add_command(cmd);
-    }
-    if !args.detached {
-        tmux = tmux.add_command(attach(&name));
+pub fn start_handler(args: &StartDirectoryArgs) {
+    let settings = Settings::load().exit_err();
+    let detached = resolve_flag(args.detached, args.attach, settings.detached);
+    let always_new_session = resolve_flag(
+        args.always_new_session,
+        args.reuse_session,
+        settings.always_new_session,
+    );
+
+    let (name, path, template, config_path) = resolve_dir_path(args);
+    if let Some(file) = find_local_project_file(&path) {
+        trust::ensure_trusted(&file).exit_err();
+        let mut project = read_local_project(&file, &path).exit_err();
+        if let Some(name) = &args.name {
+            project.name.clone_from(name);
+        }
+
+        start_project(
+            project,
+            Some(file),
+            &settings,
+            detached,
+            always_new_session,
+            Vec::new(),
+        );
+        return;
     }
 
-    tmux.output()
-        .exit(1, "Could not switch to the Tmux session");
+    let session_name = settings.session_name(&name);
+    start_session(
+        &session_name,
+        &settings,
+        detached,
+        always_new_session,
+        || {
+            directory_session(
+                &name,
+                path,
+                template,
+                config_path,
+                &settings,
+                prompt_variable,
+            )
+            .exit_err()
+        },
+    );
 }
 
-fn resolve_dir_path(cli_args: &StartDirectoryArgs) -> (String, PathBuf) {
-    let name = &cli_args.directory;
+/// The name, path and template of the directory and the directories file if it's configured there
+fn resolve_dir_path(
+    cli_args: &StartDirectoryArgs,
+) -> (String, PathBuf, Option<String>, Option<PathBuf>) {
+    let query = &cli_args.directory;
 
-    let dirs = parse_directory_config().exit_err(1);
-    let dir = dirs.get(name);
+    let dirs = parse_directory_config().exit_err();
+    let path = absolute_path(Path::new(query));
+    // Configured names win over paths, if neither exists the name is matched fuzzily
+    let dir = dirs.get(query).map(|dir| (query.clone(), dir)).or_else(|| {
+        if path.is_ok() {
+            return None;
+        }
+        let name = resolve_name(query, dirs.names().collect(), |name| name.as_str())?;
+        Some((name.clone(), dirs.get(name)?))
+    });
     let user_name = cli_args.name.clone();
 
     match dir {
-        Some(dir) => (
-            user_name.unwrap_or_else(|| name.clone()),
-            absolute_path(dir).exit(1, "The path could not be generated"),
-        ),
+        Some((name, dir)) => {
+            let path = absolute_path(&dir.path).exit(
+                ExitCode::InvalidConfig,
+                format!(
+                    "The directory {} of {name} doesn't exist",
+                    dir.path.display()
+                ),
+            );
+            (
+                user_name.unwrap_or(name),
+                path,
+                dir.template.clone(),
+                directories_path().ok(),
+            )
+        }
         None => {
-            let relative_path = PathBuf::from(&cli_args.directory);
-            let path = absolute_path(&relative_path).exit(1, "The path could not be generated");
+            let path = path.map_err(|_| not_found("Directory", query)).exit_err();
             let name = user_name.unwrap_or_else(|| dir_name(&path));
 
-            (name, path)
+            (name, path, None, None)
         }
     }
 }
@@ -0,0 +1,74 @@
+use crate::{
+    exit,
+    exit::{ExitCode, ExitErr},
+};
+use moxide::{error::MoxideError, formats::read_config_file};
+use serde::de::DeserializeOwned;
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Write},
+    path::Path,
+    process::Command,
+};
+
+/// `$VISUAL` or `$EDITOR`, arguments like in `code --wait` are allowed
+fn editor() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| String::from("vi"))
+}
+
+fn open_editor(path: &Path) {
+    let editor = editor();
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .unwrap_or_else(|err| exit!(ExitCode::Failure, "Can't run {editor}: {err}"));
+    if !status.success() {
+        exit!(ExitCode::Failure, "{editor} failed with {status}");
+    }
+}
+
+/// Whether the user wants to fix the file, everything but `r` reopens the editor
+fn ask_reopen() -> bool {
+    eprint!("Reopen the editor or roll back the changes? [E/r] ");
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(MoxideError::Input)
+        .exit_err();
+    !matches!(answer.trim(), "r" | "R")
+}
+
+/// Opens the file in the editor until it can be parsed as `T` again or the changes are rolled back
+pub fn edit_file<T: DeserializeOwned>(path: &Path) {
+    let previous = fs::read(path).map_err(MoxideError::io(path)).exit_err();
+
+    loop {
+        open_editor(path);
+
+        let Err(err) = read_config_file::<T>(path) else {
+            return;
+        };
+        eprintln!("{err}");
+
+        if !io::stdin().is_terminal() || !ask_reopen() {
+            fs::write(path, &previous)
+                .map_err(MoxideError::io(path))
+                .exit_err();
+            exit!(
+                ExitCode::InvalidConfig,
+                "Rolled back the changes of {}",
+                path.display()
+            );
+        }
+    }
+}
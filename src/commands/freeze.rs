@@ -1,10 +1,15 @@
 use crate::{
     exit,
-    helpers::{get_config_dir, Exit},
-    projects::{Project, ProjectSetup},
-    templates::Window as TemplateWindow,
+    exit::{Exit, ExitCode, ExitErr},
 };
 use itertools::Itertools;
+use moxide::{
+    formats::Format,
+    helpers::get_config_dir,
+    projects::{Project, ProjectSetup},
+    settings::Settings,
+    templates::{Pane as TemplatePane, PaneConfig, Window as TemplateWindow},
+};
 use std::{
     collections::BTreeMap,
     fmt,
@@ -18,25 +23,53 @@ const SEPERATOR: &str = "\t";
 struct Window {
     name: String,
     layout: String,
+    active: bool,
+    zoomed: bool,
     panes: Vec<PathBuf>,
+    active_pane: Option<usize>,
 }
 
 impl Window {
     #[allow(clippy::wrong_self_convention)]
     fn to_template_window(self, most_used_path: &Path) -> TemplateWindow {
+        let relative_dir = |dir: PathBuf| match dir.strip_prefix(most_used_path) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => dir,
+        };
+
+        // A directory shared by all panes is stored once for the whole window
+        let shared_dir = self
+            .panes
+            .iter()
+            .all_equal_value()
+            .ok()
+            .filter(|dir| *dir != most_used_path)
+            .cloned();
+
+        // The last pane is active by default, so only other panes need to be focused
+        let pane_count = self.panes.len();
+        let focused = self
+            .active_pane
+            .filter(|&index| index + 1 != pane_count || self.zoomed);
+
         let panes = self
             .panes
             .into_iter()
-            .map(|dir| {
-                if dir == most_used_path {
-                    String::default()
+            .enumerate()
+            .map(|(index, dir)| {
+                let is_focused = focused == Some(index);
+                let dir =
+                    (dir != most_used_path && shared_dir.is_none()).then(|| relative_dir(dir));
+
+                if dir.is_none() && !is_focused {
+                    TemplatePane::Command(String::default())
                 } else {
-                    let escaped_name = dir
-                        .display()
-                        .to_string()
-                        .replace('\\', "\\\\")
-                        .replace('"', "\\\"");
-                    format!("cd {escaped_name}")
+                    TemplatePane::Detailed(PaneConfig {
+                        dir,
+                        focus: (is_focused && !self.zoomed).then_some(true),
+                        zoom: (is_focused && self.zoomed).then_some(true),
+                        ..Default::default()
+                    })
                 }
             })
             .collect();
@@ -45,6 +78,10 @@ impl Window {
             panes,
             name: Some(self.name),
             layout: Some(self.layout),
+            dir: shared_dir.map(relative_dir),
+            env: None,
+            options: None,
+            focus: self.active.then_some(true),
         }
     }
 }
@@ -56,11 +93,14 @@ enum ActiveTmuxInstance {
         layout: String,
         index: usize,
         session_name: String,
+        active: bool,
+        zoomed: bool,
     },
     Pane {
         window_index: usize,
         cwd: PathBuf,
         session_name: String,
+        active: bool,
     },
 }
 
@@ -99,11 +139,15 @@ impl TryFrom<&str> for ActiveTmuxInstance {
                 let index: String = parts.next().ok_or(TmuxParseError::MissingData(2))?.into();
                 let index = index.parse().map_err(|_| TmuxParseError::NoNumber(index))?;
                 let session_name = parts.next().ok_or(TmuxParseError::MissingData(3))?.into();
+                let active = parts.next().ok_or(TmuxParseError::MissingData(4))? == "1";
+                let zoomed = parts.next().ok_or(TmuxParseError::MissingData(5))? == "1";
                 Ok(Self::Window {
                     name,
                     layout,
                     index,
                     session_name,
+                    active,
+                    zoomed,
                 })
             }
             "pane:" => {
@@ -115,10 +159,12 @@ impl TryFrom<&str> for ActiveTmuxInstance {
                 let cwd = parts.next().ok_or(TmuxParseError::MissingData(2))?;
                 let cwd = PathBuf::from(cwd);
                 let session_name = parts.next().ok_or(TmuxParseError::MissingData(3))?.into();
+                let active = parts.next().ok_or(TmuxParseError::MissingData(4))? == "1";
                 Ok(Self::Pane {
                     window_index,
                     cwd,
                     session_name,
+                    active,
                 })
             }
             str => Err(TmuxParseError::UndefinedPrefix(str.into())),
@@ -128,11 +174,13 @@ impl TryFrom<&str> for ActiveTmuxInstance {
 
 fn current_windows() -> Vec<Window> {
     // as far i know the name/layout/index can't include a tab
-    let window_cmd = TmuxCommand::list_windows()
-        .format("window:\t#{window_name}\t#{window_layout}\t#{window_index}\t#{session_name}");
+    let window_cmd = TmuxCommand::list_windows().format(concat!(
+        "window:\t#{window_name}\t#{window_layout}\t#{window_index}\t#{session_name}",
+        "\t#{window_active}\t#{window_zoomed_flag}"
+    ));
     let panes_cmd = TmuxCommand::list_panes()
         .all()
-        .format("pane:\t#{window_index}\t#{pane_current_path}\t#{session_name}");
+        .format("pane:\t#{window_index}\t#{pane_current_path}\t#{session_name}\t#{pane_active}");
 
     let output = Tmux::new()
         .add_command(window_cmd)
@@ -140,7 +188,7 @@ fn current_windows() -> Vec<Window> {
         .output()
         .ok()
         .and_then(|out| String::from_utf8(out.stdout()).ok())
-        .exit(1, "Can't receive current tmux session setup");
+        .exit(ExitCode::Tmux, "Can't receive current tmux session setup");
 
     let tmux_panes_windows =
         output
@@ -159,19 +207,28 @@ fn current_windows() -> Vec<Window> {
                 layout,
                 index,
                 session_name,
+                active,
+                zoomed,
             } => {
                 windows_map.entry((index, session_name)).or_insert(Window {
                     name,
                     layout,
+                    active,
+                    zoomed,
                     panes: Vec::new(),
+                    active_pane: None,
                 });
             }
             ActiveTmuxInstance::Pane {
                 window_index,
                 cwd,
                 session_name,
+                active,
             } => {
                 if let Some(window) = windows_map.get_mut(&(window_index, session_name)) {
+                    if active {
+                        window.active_pane = Some(window.panes.len());
+                    }
                     window.panes.push(cwd);
                 }
             }
@@ -185,26 +242,40 @@ trait ConfigWriter {
     fn write(&self, project: Project) -> Result<Option<String>, String>;
 }
 
-struct File(PathBuf);
+pub struct File(PathBuf, Format);
 
 impl File {
-    fn try_new<N: fmt::Display>(name: N, force: bool) -> Result<Self, String> {
+    /// A new project file, fails if the file exists unless `force` is set
+    pub fn try_new<N: fmt::Display>(
+        name: N,
+        format: Option<Format>,
+        force: bool,
+    ) -> Result<Self, String> {
         let name = name.to_string();
         if name.contains('/') {
             return Err(String::from("File name can't contain /"));
         }
 
-        let extension = Path::new(&name).extension();
-        let is_yaml = extension.is_some_and(|extension| {
-            extension.eq_ignore_ascii_case("yaml") || extension.eq_ignore_ascii_case("yml")
-        });
-        let file_name = if is_yaml {
+        let extension_format = Format::from_path(Path::new(&name));
+        let format = match (format, extension_format) {
+            (Some(format), Some(extension_format)) if format != extension_format => {
+                return Err(format!(
+                    "The file name {name} doesn't match the format {}",
+                    format.extension()
+                ));
+            }
+            (format, extension_format) => format.or(extension_format).unwrap_or_default(),
+        };
+        let file_name = if extension_format.is_some() {
             name
         } else {
-            format!("{name}.yaml")
+            format!("{name}.{}", format.extension())
         };
 
-        let file_path = get_config_dir().join("projects/").join(file_name);
+        let file_path = get_config_dir()
+            .map_err(|err| err.to_string())?
+            .join("projects/")
+            .join(file_name);
 
         if file_path.exists() && !force {
             Err(format!(
@@ -212,34 +283,44 @@ impl File {
                 file_path.display()
             ))
         } else {
-            Ok(Self(file_path))
+            Ok(Self(file_path, format))
         }
     }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn save(&self, project: &Project) -> Result<(), String> {
+        let content = self
+            .1
+            .serialize(project)
+            .map_err(|err| format!("Can't create {}: {err}", self.1.extension()))?;
+
+        std::fs::write(&self.0, content).map_err(|err| format!("Can't write file: {err}"))
+    }
 }
 
 impl ConfigWriter for File {
     fn write(&self, project: Project) -> Result<Option<String>, String> {
-        let yaml =
-            serde_yaml::to_string(&project).map_err(|err| format!("Can't create yaml: {err}"))?;
-
-        match std::fs::write(&self.0, yaml) {
-            Ok(()) => Ok(Some(format!(
-                "Froze configuration into {}",
-                self.0.display()
-            ))),
-            Err(err) => Err(format!("Can't write file: {err}")),
-        }
+        self.save(&project)?;
+        Ok(Some(format!(
+            "Froze configuration into {}",
+            self.0.display()
+        )))
     }
 }
 
-struct StdOut;
+struct StdOut(Format);
 
 impl ConfigWriter for StdOut {
     fn write(&self, project: Project) -> Result<Option<String>, String> {
-        let yaml =
-            serde_yaml::to_string(&project).map_err(|err| format!("Can't create yaml: {err}"))?;
+        let content = self
+            .0
+            .serialize(&project)
+            .map_err(|err| format!("Can't create {}: {err}", self.0.extension()))?;
 
-        println!("{yaml}");
+        println!("{content}");
         Ok(None)
     }
 }
@@ -247,54 +328,108 @@ impl ConfigWriter for StdOut {
 fn new_config_writer<T: fmt::Display>(
     stdout: bool,
     file_name: T,
+    format: Option<Format>,
     force: bool,
 ) -> Result<Box<dyn ConfigWriter>, String> {
     if stdout {
-        Ok(Box::new(StdOut))
+        Ok(Box::new(StdOut(format.unwrap_or_default())))
     } else {
-        let file = File::try_new(file_name, force)?;
+        let file = File::try_new(file_name, format, force)?;
         Ok(Box::new(file))
     }
 }
 
-pub fn freeze_handler(name: Option<String>, force: bool, file_name: Option<&str>, stdout: bool) {
+/// The windows of the current session as a project. Directories are stored relative to the root
+/// directory, which defaults to the directory most panes are in and names the project by default
+pub fn session_project(name: Option<String>, root_dir: Option<PathBuf>) -> Result<Project, String> {
     let windows = current_windows();
-    let most_used_path = windows
-        .iter()
-        .flat_map(|window| &window.panes)
-        .counts()
-        .into_iter()
-        .max_by_key(|&(_, count)| count)
-        .expect("There must be at least one pane")
-        .0
-        .clone();
-    let most_used_path = most_used_path.as_path();
+    let root_dir = match root_dir {
+        Some(root_dir) => root_dir,
+        None => windows
+            .iter()
+            .flat_map(|window| &window.panes)
+            .counts()
+            .into_iter()
+            .max_by_key(|&(_, count)| count)
+            .ok_or("The current session has no panes")?
+            .0
+            .clone(),
+    };
     let name = name
         .or_else(|| {
-            let file_name = most_used_path.file_name()?;
+            let file_name = root_dir.file_name()?;
             let str = file_name.to_str()?;
             Some(str.to_string())
         })
         .unwrap_or_else(|| String::from("Unnamed Project"));
 
-    let writer = new_config_writer(stdout, file_name.unwrap_or(&name), force)
-        .unwrap_or_else(|err| exit!(1, "{err}"));
-
     let template_wins: Vec<_> = windows
         .into_iter()
-        .map(|win| win.to_template_window(most_used_path))
+        .map(|win| win.to_template_window(&root_dir))
         .collect();
-    let proj = Project {
+    Ok(Project {
         name,
-        root_dir: most_used_path.to_path_buf(),
+        root_dir,
+        vars: None,
+        env: None,
+        env_file: None,
+        hooks: None,
+        options: None,
         setup: ProjectSetup::Windows {
             windows: template_wins,
         },
-    };
+    })
+}
+
+pub fn freeze_handler(
+    name: Option<String>,
+    force: bool,
+    file_name: Option<&str>,
+    format: Option<Format>,
+    stdout: bool,
+) {
+    let mut proj = session_project(name, None).unwrap_or_else(|err| exit!(ExitCode::Tmux, "{err}"));
+    // The project is named like the session it starts
+    proj.name = Settings::load().exit_err().session_name(&proj.name);
+
+    let writer = new_config_writer(stdout, file_name.unwrap_or(&proj.name), format, force)
+        .unwrap_or_else(|err| exit!(ExitCode::Failure, "{err}"));
 
     match writer.write(proj) {
         Ok(Some(msg)) => println!("{msg}"),
         Ok(None) => {}
-        Err(msg) => exit!(1, "{msg}"),
+        Err(msg) => exit!(ExitCode::Failure, "{msg}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_template_window_focus() {
+        let window = Window {
+            name: "Editor".to_string(),
+            layout: "tiled".to_string(),
+            active: true,
+            zoomed: true,
+            panes: vec![PathBuf::from("/code"), PathBuf::from("/code/docs")],
+            active_pane: Some(1),
+        };
+
+        let template_window = window.to_template_window(Path::new("/code"));
+
+        assert_eq!(template_window.focus, Some(true));
+        assert_eq!(
+            template_window.panes,
+            vec![
+                TemplatePane::Command(String::default()),
+                TemplatePane::Detailed(PaneConfig {
+                    dir: Some(PathBuf::from("docs")),
+                    zoom: Some(true),
+                    ..Default::default()
+                }),
+            ]
+        );
     }
 }
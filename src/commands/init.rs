@@ -1,8 +1,12 @@
-use crate::init;
+use crate::{exit, exit::ExitCode};
+use moxide::init;
 
 pub fn init_handler() {
     match init::init_config() {
         Ok(path) => println!("Initialized config in {}", path.display()),
-        Err(err) => eprintln!("Could not create files/directories: {err}"),
+        Err(err) => exit!(
+            ExitCode::Failure,
+            "Could not create files/directories: {err}"
+        ),
     }
 }
@@ -1,45 +1,63 @@
-use crate::{
-    cli::list::ListCli,
+use crate::{cli::list::ListCli, exit::ExitErr};
+use moxide::{
     directories,
-    helpers::{format_name, ExitErr},
-    projects, templates,
-    tmux::session_exists,
+    helpers::format_name,
+    projects,
+    settings::Settings,
+    templates,
+    tmux::{OriginKind, Sessions},
 };
 
 pub fn list_handler(args: ListCli) {
-    let projects = projects::parse_project_config();
+    let settings = Settings::load().exit_err();
+    let list_format = &settings.list_format;
+    let format_project = args.format_project.or_else(|| list_format.project.clone());
+    let format_template = args
+        .format_template
+        .or_else(|| list_format.template.clone());
+    let format_directory = args
+        .format_directory
+        .or_else(|| list_format.directory.clone());
+    // The sessions are only listed once and only if they're needed
+    let sessions = if args.running {
+        Sessions::fetch().exit_err()
+    } else {
+        Sessions::default()
+    };
+    // Sessions that got a suffix because of a name collision are found by their origin
+    let is_running = |kind: OriginKind, name: &str| {
+        sessions.runs(kind, name) || sessions.contains(&settings.session_name(name))
+    };
+
+    let projects = projects::project_files().exit_err();
     for project in projects {
-        if args.running && !session_exists(&project.name).unwrap_or(false) {
+        if args.running && !is_running(OriginKind::Project, &project.name) {
             continue;
         }
 
-        println!(
-            "{}",
-            format_name(args.format_project.as_deref(), &project.name)
-        );
+        println!("{}", format_name(format_project.as_deref(), &project.name));
     }
 
-    let templates = templates::parse_template_config();
+    let templates = templates::template_files().exit_err();
     for template in templates {
-        let is_hidden = template.hidden.unwrap_or(false);
-        if is_hidden && !args.all {
+        if template.hidden && !args.all {
             continue;
         }
-        if args.running && !session_exists(&template.name).unwrap_or(false) {
+        if args.running && !is_running(OriginKind::Template, &template.name) {
             continue;
         }
 
         println!(
             "{}",
-            format_name(args.format_template.as_deref(), &template.name)
+            format_name(format_template.as_deref(), &template.name)
         );
     }
 
-    let dirs = directories::parse_directory_config().exit_err(1);
+    let dirs = directories::parse_directory_config().exit_err();
     let dirs = dirs
         .names()
-        .filter(|name| !args.running || session_exists(*name).unwrap_or(false));
+        .filter(|name| !args.running || is_running(OriginKind::Directory, name));
     for name in dirs {
-        println!("{}", format_name(args.format_directory.as_deref(), name));
+        println!("{}", format_name(format_directory.as_deref(), name));
     }
 }
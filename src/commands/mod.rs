@@ -1,6 +1,13 @@
+pub mod check;
 pub mod directory;
+pub mod edit;
 pub mod freeze;
 pub mod init;
 pub mod list;
 pub mod project;
+pub mod restart;
+pub mod schema;
+pub mod status;
+pub mod stop;
 pub mod template;
+pub mod trust;
@@ -1,64 +1,178 @@
 use crate::{
-    cli::project::{ProjectCommands, ProjectStartArgs},
-    helpers::{self, apply_if_some, Exit},
-    projects::find_project,
-    projects::parse_project_config,
-    templates::apply_windows,
-    tmux,
+    cli::project::{ProjectCommands, ProjectNewArgs, ProjectStartArgs},
+    commands::{
+        edit::edit_file,
+        freeze::{session_project, File},
+    },
+    exit,
+    exit::{Exit, ExitCode, ExitErr},
+    prompt::prompt_variable,
+};
+use moxide::{
+    formats::read_config_file,
+    helpers,
+    projects::{
+        find_project_path, find_project_source, parse_project_config, project_files, Project,
+        ProjectSetup,
+    },
+    settings::{resolve_flag, Settings},
+    templates::{find_template, Pane, Window},
+    tmux::{self, Sessions},
     widgets::{heading::Heading, table::Table},
+    SessionBuilder,
 };
-use tmux_interface::{NewSession, Tmux};
+use std::path::{Path, PathBuf};
+use tmux_interface::Tmux;
 
 pub fn project_handler(action: ProjectCommands) {
     match action {
         ProjectCommands::List { minimal } => list_handler(minimal),
         ProjectCommands::Start(args) => start_handler(args),
+        ProjectCommands::New(args) => new_handler(args),
+        ProjectCommands::Edit { name } => {
+            edit_file::<Project>(&find_project_source(&name).exit_err())
+        }
     }
 }
 
 fn list_handler(minimal: bool) {
-    for proj in parse_project_config() {
+    let table_style = Settings::load().exit_err().table_style;
+
+    for proj in parse_project_config().exit_err() {
         if minimal {
             println!("{}", proj.name);
         } else {
             println!("{}", Heading(proj.name));
-            println!("{}", Table::from(proj.setup));
+            let table = Table::try_from(proj.setup).exit_err();
+            println!("{}", table.with_style(table_style));
         }
     }
 }
 
 fn start_handler(args: ProjectStartArgs) {
-    let project = find_project(&args.name).exit(1, "Project could not be found");
+    let path = find_project_path(&args.name).exit_err();
+    let project = read_config_file(&path).exit_err();
+    let settings = Settings::load().exit_err();
 
-    let detached = args.detached;
+    let detached = resolve_flag(args.detached, args.attach, settings.detached);
+    let always_new_session = resolve_flag(
+        args.always_new_session,
+        args.reuse_session,
+        settings.always_new_session,
+    );
 
-    if tmux::session_exists(&project.name).unwrap_or(false) && !args.always_new_session {
-        apply_if_some(
-            Tmux::new(),
-            (!detached).then(|| tmux::attach(&project.name)),
-            |tmux, cmd| tmux.add_command(cmd),
-        )
-        .output()
-        .exit(1, "Could not attach to the Tmux-session");
+    start_project(
+        project,
+        Some(path),
+        &settings,
+        detached,
+        always_new_session,
+        args.vars,
+    );
+}
+
+/// Starts the session, or attaches to the session with that name if it's already running. The
+/// session is only built if it's started
+pub fn start_session(
+    name: &str,
+    settings: &Settings,
+    detached: bool,
+    always_new_session: bool,
+    session: impl FnOnce() -> SessionBuilder,
+) {
+    let sessions = Sessions::fetch().exit_err();
+    if sessions.contains(name) && !always_new_session {
+        if !detached {
+            tmux::run(Tmux::with_command(tmux::attach(name))).exit_err();
+        }
         return;
     }
 
-    let name = tmux::get_unused_name(&project.name);
-    let windows = Vec::from(project.setup);
-    let path = helpers::absolute_path(&project.root_dir).exit(1, "The path could not be found");
+    session()
+        .attach(!detached)
+        .name_collision(settings.name_collision)
+        .sessions(sessions)
+        .start()
+        .exit_err();
+}
 
-    let new_session_cmd = NewSession::new()
-        .detached()
-        .session_name(&name)
-        .start_directory(path.to_string_lossy().into_owned());
+/// Starts the session of a project, or attaches to it if it's already running
+pub fn start_project(
+    project: Project,
+    config_path: Option<PathBuf>,
+    settings: &Settings,
+    detached: bool,
+    always_new_session: bool,
+    vars: Vec<(String, String)>,
+) {
+    let name = settings.session_name(&project.name);
+    start_session(&name, settings, detached, always_new_session, || {
+        project
+            .session(config_path, settings, vars, prompt_variable)
+            .exit_err()
+    });
+}
 
-    let initial_tmux = apply_if_some(
-        Tmux::new().add_command(new_session_cmd),
-        (!detached).then(|| tmux::attach(&name)),
-        |tmux, cmd| tmux.add_command(cmd),
-    );
+fn new_handler(args: ProjectNewArgs) {
+    let root_dir = args.dir.map(|dir| {
+        helpers::absolute_path(&dir).exit(
+            ExitCode::Failure,
+            format!("The directory {} doesn't exist", dir.display()),
+        )
+    });
+
+    let project = if args.from_session {
+        session_project(Some(args.name), root_dir)
+            .unwrap_or_else(|err| exit!(ExitCode::Tmux, "{err}"))
+    } else {
+        let root_dir = root_dir.unwrap_or_else(|| {
+            helpers::absolute_path(Path::new("."))
+                .exit(ExitCode::Failure, "Can't read the current directory")
+        });
+        let setup = match args.template {
+            // The template is resolved so a misspelled name doesn't end up in the file
+            Some(template) => ProjectSetup::Template(find_template(&template).exit_err().name),
+            None => ProjectSetup::Windows {
+                windows: vec![Window {
+                    name: None,
+                    layout: None,
+                    dir: None,
+                    env: None,
+                    options: None,
+                    focus: None,
+                    panes: vec![Pane::Command(String::new())],
+                }],
+            },
+        };
+
+        Project {
+            name: args.name,
+            root_dir,
+            vars: None,
+            env: None,
+            env_file: None,
+            hooks: None,
+            options: None,
+            setup,
+        }
+    };
+
+    let file = File::try_new(&project.name, args.format, args.force)
+        .unwrap_or_else(|err| exit!(ExitCode::Failure, "{err}"));
+    let existing = project_files()
+        .exit_err()
+        .into_iter()
+        .find(|existing| existing.name == project.name && existing.path != file.path());
+    if let Some(existing) = existing {
+        exit!(
+            ExitCode::Failure,
+            "The project {} is already defined in {}",
+            project.name,
+            existing.path.display()
+        );
+    }
 
-    apply_windows(initial_tmux, &windows, Some(&path))
-        .output()
-        .exit(1, "Could not start Tmux-session");
+    file.save(&project)
+        .unwrap_or_else(|err| exit!(ExitCode::Failure, "{err}"));
+    println!("Created {}", file.path().display());
 }
@@ -0,0 +1,151 @@
+use crate::{exit::ExitErr, prompt::prompt_variable};
+use moxide::{
+    directories::{directories_path, directory_session, parse_directory_config},
+    error::MoxideError,
+    formats::read_config_file,
+    helpers::absolute_path,
+    matching::{not_found, resolve_name},
+    projects::{
+        find_local_project_file, find_project_path, project_files, read_local_project, Project,
+    },
+    settings::{resolve_flag, Settings},
+    templates::{find_template_path, read_template, template_files},
+    tmux::{self, NameCollision, OriginKind, Sessions},
+    trust, SessionBuilder,
+};
+use std::{collections::HashMap, time::Duration};
+
+/// How long the panes of the old session may take to stop
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The project, template or directory the user meant, projects win over templates which win over
+/// directories with the same name
+fn resolve_definition(query: &str) -> Result<(OriginKind, String), MoxideError> {
+    let projects = project_files()?
+        .into_iter()
+        .map(|file| (OriginKind::Project, file.name));
+    let templates = template_files()?
+        .into_iter()
+        .map(|file| (OriginKind::Template, file.name));
+    let dirs = parse_directory_config()?
+        .into_iter()
+        .map(|(name, _)| (OriginKind::Directory, name));
+
+    let candidates = projects.chain(templates).chain(dirs).collect();
+    resolve_name(query, candidates, |(_, name)| name).ok_or_else(|| not_found("Session", query))
+}
+
+/// The running session of the definition, the session with its usual name wins over sessions
+/// that were renamed
+fn running_session(
+    kind: OriginKind,
+    name: &str,
+    sessions: &Sessions,
+    settings: &Settings,
+) -> Option<String> {
+    let session_name = settings.session_name(name);
+    let running = sessions.sessions_of(kind, name, &session_name);
+    running
+        .iter()
+        .find(|running| **running == session_name)
+        .or_else(|| running.first())
+        .map(|running| (*running).to_string())
+}
+
+/// The session of the definition, it replaces `running` if that's set
+fn definition_session(
+    kind: OriginKind,
+    name: &str,
+    running: Option<&str>,
+    sessions: &Sessions,
+    settings: &Settings,
+) -> Result<SessionBuilder, MoxideError> {
+    let session = match kind {
+        OriginKind::Project => {
+            let path = find_project_path(name)?;
+            let project: Project = read_config_file(&path)?;
+            project.session(Some(path), settings, Vec::new(), prompt_variable)?
+        }
+        OriginKind::Template => {
+            let path = find_template_path(name)?;
+            let template = read_template(&path)?;
+            // The template is restarted in the directory it was started in
+            let session_name = running.map_or_else(|| settings.session_name(name), str::to_string);
+            let root = running
+                .and_then(|running| sessions.origin(running))
+                .and_then(|origin| origin.root.clone());
+            let dir = match root {
+                Some(root) => Some(root),
+                None => tmux::session_path(&session_name)?,
+            };
+            template.session(
+                Some(path),
+                session_name,
+                dir,
+                HashMap::new(),
+                prompt_variable,
+            )?
+        }
+        OriginKind::Directory => {
+            let dirs = parse_directory_config()?;
+            let dir = dirs.get(name).ok_or_else(|| not_found("Directory", name))?;
+            let path = absolute_path(&dir.path).map_err(MoxideError::io(&dir.path))?;
+
+            match find_local_project_file(&path) {
+                Some(file) => {
+                    trust::ensure_trusted(&file)?;
+                    let project = read_local_project(&file, &path)?;
+                    project.session(Some(file), settings, Vec::new(), prompt_variable)?
+                }
+                None => {
+                    let config_path = directories_path().ok();
+                    directory_session(
+                        name,
+                        path,
+                        dir.template.clone(),
+                        config_path,
+                        settings,
+                        prompt_variable,
+                    )?
+                }
+            }
+        }
+    };
+
+    Ok(match running {
+        Some(running) => session.rename(running),
+        None => session,
+    })
+}
+
+pub fn restart_handler(name: &str, detached: bool, attach: bool) {
+    let settings = Settings::load().exit_err();
+    let detached = resolve_flag(detached, attach, settings.detached);
+
+    let (kind, name) = resolve_definition(name).exit_err();
+    let sessions = Sessions::fetch().exit_err();
+    let running = running_session(kind, &name, &sessions, &settings);
+    let session =
+        definition_session(kind, &name, running.as_deref(), &sessions, &settings).exit_err();
+    let Some(session_name) = running else {
+        session
+            .attach(!detached)
+            .sessions(sessions)
+            .start()
+            .exit_err();
+        return;
+    };
+
+    // The new session is started before the old one is killed, so the clients can be moved to it.
+    // The panes of the old session are stopped first, so servers free their ports
+    let old_name = tmux::move_aside(&session_name, &sessions).exit_err();
+    tmux::stop_panes(&old_name, STOP_TIMEOUT).exit_err();
+    let started = session.name_collision(NameCollision::Fail).start();
+    if let Err(err) = started {
+        // The old session keeps running if the new one can't be started
+        let _ = tmux::rename_session(&old_name, &session_name);
+        Err::<(), _>(err).exit_err();
+    }
+
+    tmux::replace_session(&old_name, &session_name, !detached).exit_err();
+}
@@ -0,0 +1,23 @@
+use crate::{cli::schema::SchemaKind, exit, exit::ExitCode};
+use moxide::{Directories, Project, Settings, Template};
+use schemars::{generate::SchemaSettings, JsonSchema};
+
+fn schema_for<T: JsonSchema>() -> String {
+    let schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    serde_json::to_string_pretty(&schema)
+        .unwrap_or_else(|err| exit!(ExitCode::Failure, "Can't create the schema: {err}"))
+}
+
+pub fn schema_handler(kind: SchemaKind) {
+    let schema = match kind {
+        SchemaKind::Project => schema_for::<Project>(),
+        SchemaKind::Template => schema_for::<Template>(),
+        SchemaKind::Directories => schema_for::<Directories>(),
+        SchemaKind::Config => schema_for::<Settings>(),
+    };
+
+    println!("{schema}");
+}
@@ -0,0 +1,44 @@
+use crate::exit::ExitErr;
+use moxide::{
+    settings::Settings,
+    tmux::SessionStatus,
+    widgets::{heading::Heading, table::Table},
+};
+use std::path::Path;
+
+fn display_path(path: Option<&Path>) -> String {
+    path.map_or_else(|| "None".to_string(), |path| path.display().to_string())
+}
+
+pub fn status_handler() {
+    let table_style = Settings::load().exit_err().table_style;
+    let mut sessions = SessionStatus::fetch().exit_err();
+    sessions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for session in sessions {
+        let origin = session.origin.as_ref();
+        let table = Table::new(vec![
+            (
+                "Origin",
+                origin.map_or_else(
+                    || "None".to_string(),
+                    |origin| format!("{} {}", origin.kind, origin.name),
+                ),
+            ),
+            (
+                "Root",
+                display_path(origin.and_then(|origin| origin.root.as_deref())),
+            ),
+            (
+                "Config",
+                display_path(origin.and_then(|origin| origin.config_path.as_deref())),
+            ),
+            ("Windows", session.windows.to_string()),
+            ("Clients", session.attached.to_string()),
+            ("Created", session.created),
+        ]);
+
+        println!("{}", Heading(session.name));
+        println!("{}", table.with_style(table_style));
+    }
+}
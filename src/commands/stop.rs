@@ -0,0 +1,66 @@
+use crate::exit::ExitErr;
+use itertools::Itertools;
+use moxide::{
+    directories::parse_directory_config,
+    error::MoxideError,
+    matching::{not_found, resolve_target},
+    projects::project_files,
+    settings::Settings,
+    templates::template_files,
+    tmux::{self, OriginKind, Sessions},
+};
+use std::time::Duration;
+
+/// Finds the running session of a project, template or directory, or a session by its own name.
+/// Definitions are mapped to their sessions first, so a session only counts once
+fn resolve_session(query: &str, sessions: &Sessions) -> Result<String, MoxideError> {
+    let settings = Settings::load()?;
+    let projects = project_files()?
+        .into_iter()
+        .map(|file| (OriginKind::Project, file.name));
+    let templates = template_files()?
+        .into_iter()
+        .map(|file| (OriginKind::Template, file.name));
+    let directories = parse_directory_config()?
+        .into_iter()
+        .map(|(name, _)| (OriginKind::Directory, name));
+
+    let definitions = projects
+        .chain(templates)
+        .chain(directories)
+        .flat_map(|(kind, name)| {
+            sessions
+                .sessions_of(kind, &name, &settings.session_name(&name))
+                .into_iter()
+                .map(|session| (name.clone(), session.to_string()))
+                .collect::<Vec<_>>()
+        });
+    let candidates: Vec<_> = sessions
+        .names()
+        .map(|name| (name.to_string(), name.to_string()))
+        .chain(definitions)
+        .collect();
+
+    resolve_target(query, &candidates).ok_or_else(|| not_found("Session", query))
+}
+
+pub fn stop_handler(name: Option<&str>, all: bool, timeout: u64) {
+    let sessions = Sessions::fetch().exit_err();
+    let session_names = if all {
+        // Killing the session moxide runs in may kill moxide, so it's stopped last
+        let current = tmux::current_session().exit_err();
+        sessions
+            .managed()
+            .sorted_by_key(|name| (current.as_deref() == Some(name), *name))
+            .map(str::to_string)
+            .collect()
+    } else {
+        let query = name.expect("clap requires a name without --all");
+        vec![resolve_session(query, &sessions).exit_err()]
+    };
+
+    for session_name in session_names {
+        tmux::stop_session(&session_name, Duration::from_secs(timeout)).exit_err();
+        println!("Stopped {session_name}");
+    }
+}
@@ -1,23 +1,33 @@
 use crate::{
     cli::template::{StartTemplateArgs, TemplateCommands},
+    commands::{edit::edit_file, project::start_session},
+    exit::ExitErr,
+    prompt::prompt_variable,
+};
+use moxide::{
     directories::parse_directory_config,
-    helpers::{absolute_path, apply_if_some, dir_name, Exit, ExitErr},
-    templates::{apply_windows, find_template, parse_template_config},
-    tmux,
+    helpers::{absolute_path, dir_name},
+    settings::{resolve_flag, Settings},
+    templates::{
+        find_template_path, find_template_source, parse_template_config, read_template, Template,
+    },
     widgets::{heading::Heading, table::Table},
 };
 use std::path::PathBuf;
-use tmux_interface::{NewSession, Tmux, TmuxCommand};
 
 pub fn template_handler(action: TemplateCommands) {
     match action {
         TemplateCommands::List { minimal, all } => list_handler(minimal, all),
         TemplateCommands::Start(args) => start_handler(args),
+        TemplateCommands::Edit { name } => {
+            edit_file::<Template>(&find_template_source(&name).exit_err());
+        }
     }
 }
 
 fn list_handler(minimal: bool, all: bool) {
-    let templates = parse_template_config();
+    let templates = parse_template_config().exit_err();
+    let table_style = Settings::load().exit_err().table_style;
 
     for template in templates {
         let is_hidden = template.hidden.unwrap_or(false);
@@ -29,76 +39,50 @@ fn list_handler(minimal: bool, all: bool) {
             println!("{}", template.name);
         } else {
             println!("{}", Heading(template.name));
-            println!("{}", template.windows.iter().collect::<Table<_, _>>());
+            let table: Table<_, _> = template.windows.iter().collect();
+            println!("{}", table.with_style(table_style));
         }
     }
 }
 
 fn start_handler(args: StartTemplateArgs) {
-    let template = find_template(&args.template_name).exit(1, "No template found");
+    let path = find_template_path(&args.template_name).exit_err();
+    let template = read_template(&path).exit_err();
+    let settings = Settings::load().exit_err();
 
-    let detached = args.detached;
+    let detached = resolve_flag(args.detached, args.attach, settings.detached);
+    let always_new_session = resolve_flag(
+        args.always_new_session,
+        args.reuse_session,
+        settings.always_new_session,
+    );
 
     let resolved_path = args.directory.and_then(|dir| {
-        let dirs = parse_directory_config().exit_err(1);
+        let dirs = parse_directory_config().exit_err();
         let path = match dirs.get(&dir) {
-            Some(path) => path,
+            Some(entry) => &entry.path,
             None => &PathBuf::from(dir),
         };
         absolute_path(path).ok()
     });
 
-    let name = resolved_path
-        .as_ref()
-        .map_or(template.name, |p| dir_name(p));
-
-    if tmux::session_exists(&name).unwrap_or(false) && !args.always_new_session {
-        apply_if_some(
-            Tmux::new(),
-            (!detached).then(|| tmux::attach(&name)),
-            |tmux, cmd| tmux.add_command(cmd),
-        )
-        .output()
-        .exit(1, "Could not attach to the Tmux-session");
-        return;
-    }
-
-    let (new_session_cmd, name) = resolve_cmd_name(resolved_path.as_ref(), args.name, name);
-
-    let initial_tmux = apply_if_some(
-        Tmux::new().add_command(new_session_cmd),
-        (!detached).then(|| tmux::attach(&name)),
-        |tmux, cmd| tmux.add_command(cmd),
-    );
-
-    let tmux = apply_windows(initial_tmux, &template.windows, resolved_path.as_ref());
-
-    tmux.output().exit(1, "Could not start Tmux-session");
-}
-
-fn resolve_cmd_name(
-    path: Option<&PathBuf>,
-    name: Option<String>,
-    template_name: String,
-) -> (TmuxCommand<'static>, String) {
-    if let Some(p) = path {
-        let session_name = tmux::get_unused_name(&name.unwrap_or_else(|| dir_name(p)));
-        return (
-            NewSession::new()
-                .detached()
-                .session_name(session_name.clone())
-                .start_directory(p.to_string_lossy().into_owned())
-                .into(),
-            session_name,
-        );
-    }
+    let name = args.name.unwrap_or_else(|| {
+        resolved_path
+            .as_ref()
+            .map_or_else(|| template.name.clone(), |p| dir_name(p))
+    });
+    let name = settings.session_name(&name);
 
-    let session_name = tmux::get_unused_name(&name.unwrap_or(template_name));
-    (
-        NewSession::new()
-            .detached()
-            .session_name(session_name.clone())
-            .into(),
-        session_name,
-    )
+    let vars = args.vars.into_iter().collect();
+    start_session(&name, &settings, detached, always_new_session, || {
+        template
+            .session(
+                Some(path),
+                name.clone(),
+                resolved_path,
+                vars,
+                prompt_variable,
+            )
+            .exit_err()
+    });
 }
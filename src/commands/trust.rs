@@ -0,0 +1,27 @@
+use crate::{
+    exit,
+    exit::{Exit, ExitCode, ExitErr},
+};
+use moxide::{helpers::absolute_path, projects::find_local_project_file, trust};
+use std::path::Path;
+
+pub fn trust_handler(path: &Path) {
+    let path = absolute_path(path).exit(
+        ExitCode::NotFound,
+        format!("{} doesn't exist", path.display()),
+    );
+    let file = if path.is_file() {
+        path
+    } else {
+        find_local_project_file(&path).unwrap_or_else(|| {
+            exit!(
+                ExitCode::NotFound,
+                "{} contains no .moxide.yaml or .moxide/project.yaml",
+                path.display()
+            )
+        })
+    };
+
+    trust::trust(&file).exit_err();
+    println!("Trusted {}", file.display());
+}
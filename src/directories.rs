@@ -1,33 +1,104 @@
 use crate::{
-    helpers::{get_config_dir, Exit},
+    environment::load_env,
+    error::MoxideError,
+    helpers::{dir_name, get_config_dir},
+    session::SessionBuilder,
+    settings::Settings,
+    templates::find_template,
+    tmux::OriginKind,
+    variables::{resolve_variables, substitute_windows, Variable},
     widgets::table::Table,
 };
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
 use std::{
+    borrow::Cow,
     collections::{
         hash_map::{Entry, Keys},
         HashMap,
     },
-    fs,
-    path::PathBuf,
+    fmt, fs,
+    ops::Range,
+    path::{Path, PathBuf},
 };
 use thiserror::Error;
 
-#[derive(Debug, Default, Clone)]
-pub struct Directories(HashMap<String, PathBuf>);
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Directory {
+    pub path: PathBuf,
+    /// A template that is applied when the directory is started
+    pub template: Option<String>,
+}
+
+impl From<PathBuf> for Directory {
+    fn from(path: PathBuf) -> Self {
+        Self {
+            path,
+            template: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Directories(HashMap<String, Directory>);
 
 impl Directories {
-    pub fn get(&self, name: &str) -> Option<&PathBuf> {
+    pub fn get(&self, name: &str) -> Option<&Directory> {
         self.0.get(name)
     }
 
-    pub fn names(&self) -> Keys<String, PathBuf> {
+    pub fn names(&self) -> Keys<'_, String, Directory> {
         self.0.keys()
     }
 }
 
+/// The list and the map form of the directories file, the legacy format isn't valid yaml
+impl JsonSchema for Directories {
+    fn schema_name() -> Cow<'static, str> {
+        "Directories".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let path = generator.subschema_for::<PathBuf>();
+        let template = generator.subschema_for::<Option<String>>();
+        let directory = generator.subschema_for::<Directory>();
+
+        json_schema!({
+            "oneOf": [
+                {
+                    "type": "array",
+                    "items": {
+                        "oneOf": [
+                            path,
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "name": generator.subschema_for::<String>(),
+                                    "path": path,
+                                    "template": template,
+                                },
+                                "required": ["path"],
+                                "additionalProperties": false,
+                            },
+                        ]
+                    },
+                },
+                {
+                    "type": "object",
+                    "additionalProperties": { "oneOf": [path, directory] },
+                },
+            ]
+        })
+    }
+}
+
 impl std::iter::IntoIterator for Directories {
-    type Item = (String, PathBuf);
-    type IntoIter = std::collections::hash_map::IntoIter<String, PathBuf>;
+    type Item = (String, Directory);
+    type IntoIter = std::collections::hash_map::IntoIter<String, Directory>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
@@ -38,7 +109,13 @@ impl From<Directories> for Table<String, String> {
     fn from(value: Directories) -> Self {
         value
             .into_iter()
-            .map(|(name, path)| (name, path.display().to_string()))
+            .map(|(name, dir)| {
+                let path = dir.path.display().to_string();
+                match dir.template {
+                    Some(template) => (name, format!("{path} ({template})")),
+                    None => (name, path),
+                }
+            })
             .collect()
     }
 }
@@ -52,34 +129,257 @@ pub enum ParseDirectoryError {
         name: String,
         values: (PathBuf, PathBuf),
     },
+    #[error("{name:?} can't be written to a directories file in the legacy format")]
+    LegacyEntry { name: String },
+    #[error("Directories files in flow style like `[~/a, ~/b]` have to be edited by hand")]
+    FlowStyle,
+    #[error("The directories file can't be edited without changing other entries")]
+    UnexpectedEdit,
+    #[error("Invalid directories config: {0}")]
+    Yaml(#[from] serde_yaml::Error),
 }
 
-pub fn parse_directory_config() -> Result<Directories, ParseDirectoryError> {
-    let file_content = fs::read_to_string(get_config_dir().join("directories.yaml"))
-        .exit(1, "Can't read directories config file");
+/// A bare `~` is null in yaml, the legacy format read it as the home directory
+const HOME_PATH: &str = "~";
 
-    let mut hm = HashMap::new();
+/// One item of the list form, either a bare path or a mapping with an optional name
+#[derive(Debug)]
+struct ListEntry {
+    name: Option<String>,
+    directory: Directory,
+}
 
-    for line in file_content.lines() {
-        // Comments
-        if line.starts_with('#') {
-            continue;
+impl<'de> Deserialize<'de> for ListEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct RawEntry {
+            name: Option<String>,
+            path: PathBuf,
+            template: Option<String>,
         }
 
-        let (name, dir) = match line.split_once(':') {
-            Some((name, path)) => (name.trim().to_string(), PathBuf::from(path.trim())),
-            None => {
-                let path = PathBuf::from(line.trim());
-                let name = path.file_name().and_then(|name| name.to_str());
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = ListEntry;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a path or a mapping with a path")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(ListEntry {
+                    name: None,
+                    directory: PathBuf::from(value).into(),
+                })
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                self.visit_str(HOME_PATH)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let raw = RawEntry::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(ListEntry {
+                    name: raw.name,
+                    directory: Directory {
+                        path: raw.path,
+                        template: raw.template,
+                    },
+                })
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+/// A value of the map form, either a bare path or a mapping with a path
+struct MapValue(Directory);
+
+impl<'de> Deserialize<'de> for MapValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = MapValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a path or a mapping with a path")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(MapValue(PathBuf::from(value).into()))
+            }
 
-                if let Some(name) = name {
-                    (name.to_string(), path)
-                } else {
-                    return Err(ParseDirectoryError::NoDirName { dir: path.clone() });
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                self.visit_str(HOME_PATH)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                Directory::deserialize(de::value::MapAccessDeserializer::new(map)).map(MapValue)
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// The supported shapes of `directories.yaml`
+enum DirectoryFile {
+    List(Vec<ListEntry>),
+    Map(Vec<(String, Directory)>),
+}
+
+impl<'de> Deserialize<'de> for DirectoryFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FileVisitor;
+
+        impl<'de> Visitor<'de> for FileVisitor {
+            type Value = DirectoryFile;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of directories or a mapping from names to directories")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(DirectoryFile::List(Vec::new()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = seq.next_element()? {
+                    entries.push(entry);
                 }
+                Ok(DirectoryFile::List(entries))
             }
-        };
 
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some((name, MapValue(dir))) = map.next_entry()? {
+                    entries.push((name, dir));
+                }
+                Ok(DirectoryFile::Map(entries))
+            }
+        }
+
+        deserializer.deserialize_any(FileVisitor)
+    }
+}
+
+/// The legacy format has one `name: path` or bare path per line, bare paths on multiple lines
+/// are no valid yaml so they are detected before the yaml parser runs
+fn is_legacy_format(content: &str) -> bool {
+    let lines: Vec<_> = content
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    lines.is_empty()
+        || (lines.iter().any(|line| !line.contains(':'))
+            && lines
+                .iter()
+                .all(|line| !line.starts_with(|c: char| c.is_whitespace() || c == '-')))
+}
+
+/// Every line is a `name: path` that could be read as yaml as well as in the legacy format
+fn is_flat_mapping(content: &str) -> bool {
+    content.lines().filter(|line| is_content(line)).all(|line| {
+        line.contains(':') && !line.starts_with(|c: char| c.is_whitespace() || c == '-')
+    })
+}
+
+/// Yaml reads ` #` as the start of a comment, the legacy format kept it in the path
+fn has_inline_hash(content: &str) -> bool {
+    content
+        .lines()
+        .filter(|line| is_content(line))
+        .any(|line| line.contains(" #"))
+}
+
+fn parse_legacy(content: &str) -> Result<Vec<(String, Directory)>, ParseDirectoryError> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .map(|line| match line.split_once(':') {
+            Some((name, path)) => Ok((name.trim().to_string(), PathBuf::from(path.trim()).into())),
+            None => {
+                let path = PathBuf::from(line.trim());
+                Ok((directory_name(&path)?, path.into()))
+            }
+        })
+        .collect()
+}
+
+/// The name of a directory without an explicit name
+pub fn directory_name(path: &Path) -> Result<String, ParseDirectoryError> {
+    match path.file_name().and_then(|name| name.to_str()) {
+        Some(_) => Ok(dir_name(path)),
+        None => Err(ParseDirectoryError::NoDirName {
+            dir: path.to_path_buf(),
+        }),
+    }
+}
+
+fn parse_yaml(content: &str) -> Result<Vec<(String, Directory)>, ParseDirectoryError> {
+    match serde_yaml::from_str(content)? {
+        DirectoryFile::Map(entries) => Ok(entries),
+        DirectoryFile::List(entries) => entries
+            .into_iter()
+            .map(|ListEntry { name, directory }| match name {
+                Some(name) => Ok((name, directory)),
+                None => Ok((directory_name(&directory.path)?, directory)),
+            })
+            .collect(),
+    }
+}
+
+/// Files with only `name: path` lines are read as yaml, unless they only work in the legacy
+/// format they were written for
+fn reads_as_legacy(content: &str) -> bool {
+    is_legacy_format(content)
+        || (is_flat_mapping(content) && (has_inline_hash(content) || parse_yaml(content).is_err()))
+}
+
+fn parse_entries(content: &str) -> Result<Vec<(String, Directory)>, ParseDirectoryError> {
+    if reads_as_legacy(content) {
+        parse_legacy(content)
+    } else {
+        parse_yaml(content)
+    }
+}
+
+pub fn parse_directories(content: &str) -> Result<Directories, ParseDirectoryError> {
+    let entries = parse_entries(content)?;
+
+    let mut hm = HashMap::new();
+    for (name, dir) in entries {
         match hm.entry(name) {
             Entry::Vacant(entry) => {
                 entry.insert(dir);
@@ -87,7 +387,7 @@ pub fn parse_directory_config() -> Result<Directories, ParseDirectoryError> {
             Entry::Occupied(entry) => {
                 return Err(ParseDirectoryError::DuplicateName {
                     name: entry.key().clone(),
-                    values: (entry.get().clone(), dir),
+                    values: (entry.get().path.clone(), dir.path),
                 });
             }
         }
@@ -95,3 +395,386 @@ pub fn parse_directory_config() -> Result<Directories, ParseDirectoryError> {
 
     Ok(Directories(hm))
 }
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_content(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+/// The lines of the top-level entries, an entry continues on the lines that are indented further.
+/// Comments and blank lines between entries belong to none of them
+fn entry_spans(lines: &[&str]) -> Vec<Range<usize>> {
+    let Some(root) = lines.iter().find(|line| is_content(line)) else {
+        return Vec::new();
+    };
+    let root_indentation = indentation(root);
+
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if !is_content(line) && !line.trim().starts_with('#') {
+            continue;
+        }
+        match spans.last_mut() {
+            Some(span) if span.end == index && indentation(line) > root_indentation => {
+                span.end += 1;
+            }
+            _ if is_content(line) => spans.push(index..index + 1),
+            _ => {}
+        }
+    }
+    spans
+}
+
+/// Quotes a scalar if yaml needs it. Values with line breaks are written as a double-quoted json
+/// string, which is valid yaml on a single line
+fn yaml_scalar(value: &str) -> Result<String, ParseDirectoryError> {
+    let yaml = serde_yaml::to_string(value)?;
+    let yaml = yaml.trim_end();
+    if yaml.contains('\n') {
+        return Ok(serde_json::Value::from(value).to_string());
+    }
+    Ok(yaml.to_string())
+}
+
+/// A `name: path` line of the legacy format, it has no quoting so the entry has to be read back
+/// the way it was written
+fn legacy_entry(name: &str, path: &str) -> Result<String, ParseDirectoryError> {
+    let is_line = |value: &str| !value.contains(['\n', '\r']) && value.trim() == value;
+    if !is_line(name) || !is_line(path) || name.contains(':') || name.starts_with('#') {
+        return Err(ParseDirectoryError::LegacyEntry {
+            name: name.to_string(),
+        });
+    }
+    Ok(format!("{name}: {path}"))
+}
+
+/// The directories of a file that can be edited line by line, flow style collections can't
+fn editable_directories(content: &str) -> Result<Directories, ParseDirectoryError> {
+    let is_flow_style = content
+        .lines()
+        .find(|line| is_content(line))
+        .is_some_and(|line| line.trim_start().starts_with(['[', '{']));
+    if is_flow_style {
+        return Err(ParseDirectoryError::FlowStyle);
+    }
+    parse_directories(content)
+}
+
+/// Appends a directory in the form the file already uses, the comments and the order of the
+/// other entries are kept
+pub fn add_directory(
+    content: &str,
+    name: &str,
+    path: &Path,
+) -> Result<String, ParseDirectoryError> {
+    let old = editable_directories(content)?;
+    let lines: Vec<_> = content.lines().collect();
+    let indent = lines
+        .iter()
+        .find(|line| is_content(line))
+        .map_or("", |line| &line[..indentation(line)]);
+    let directory = Directory::from(path.to_path_buf());
+    let path = path.to_string_lossy();
+
+    let entry = if lines.iter().all(|line| !is_content(line)) {
+        format!(
+            "- name: {}\n  path: {}",
+            yaml_scalar(name)?,
+            yaml_scalar(&path)?
+        )
+    } else if reads_as_legacy(content) {
+        legacy_entry(name, &path)?
+    } else {
+        match serde_yaml::from_str(content)? {
+            DirectoryFile::List(_) => format!(
+                "{indent}- name: {}\n{indent}  path: {}",
+                yaml_scalar(name)?,
+                yaml_scalar(&path)?
+            ),
+            DirectoryFile::Map(_) => {
+                format!("{indent}{}: {}", yaml_scalar(name)?, yaml_scalar(&path)?)
+            }
+        }
+    };
+
+    let separator = if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    let new_content = format!("{content}{separator}{entry}\n");
+
+    // Catches duplicate names and entries that are read differently than they were written
+    let mut expected = old;
+    expected.0.insert(name.to_string(), directory);
+    if parse_directories(&new_content)? != expected {
+        return Err(ParseDirectoryError::UnexpectedEdit);
+    }
+    Ok(new_content)
+}
+
+/// Removes the entry of a directory, `None` if there's no directory with that name
+pub fn remove_directory(content: &str, name: &str) -> Result<Option<String>, ParseDirectoryError> {
+    let mut expected = editable_directories(content)?;
+    if expected.0.remove(name).is_none() {
+        return Ok(None);
+    }
+
+    let lines: Vec<_> = content.lines().collect();
+    let span = entry_spans(&lines).into_iter().find(|span| {
+        parse_directories(&lines[span.clone()].join("\n"))
+            .is_ok_and(|dirs| dirs.get(name).is_some())
+    });
+    let Some(span) = span else {
+        return Err(ParseDirectoryError::UnexpectedEdit);
+    };
+
+    let remaining = lines[..span.start].iter().chain(&lines[span.end..]);
+    let new_content: String = remaining.map(|line| format!("{line}\n")).collect();
+    if parse_directories(&new_content)? != expected {
+        return Err(ParseDirectoryError::UnexpectedEdit);
+    }
+    Ok(Some(new_content))
+}
+
+pub fn directories_path() -> Result<PathBuf, MoxideError> {
+    Ok(get_config_dir()?.join("directories.yaml"))
+}
+
+pub fn parse_directory_config() -> Result<Directories, MoxideError> {
+    let path = directories_path()?;
+    let file_content = fs::read_to_string(&path).map_err(MoxideError::io(path))?;
+
+    Ok(parse_directories(&file_content)?)
+}
+
+/// The session of the directory `name` at `path`, it uses the template of the directory or the
+/// default template. `config_path` is the directories file if the directory is configured there
+pub fn directory_session(
+    name: &str,
+    path: PathBuf,
+    template: Option<String>,
+    config_path: Option<PathBuf>,
+    settings: &Settings,
+    prompt: impl FnMut(&str, &Variable) -> Result<String, MoxideError>,
+) -> Result<SessionBuilder, MoxideError> {
+    let builder = SessionBuilder::new(settings.session_name(name))
+        .origin(OriginKind::Directory, name, config_path)
+        .window_name(name);
+    let builder = match template.or_else(|| settings.default_template.clone()) {
+        Some(template) => {
+            let template = find_template(&template)?;
+            let values = resolve_variables(template.variables.as_ref(), HashMap::new(), prompt)?;
+            let env = load_env(template.env, template.env_file.as_deref(), Some(&path))?;
+
+            builder
+                .windows(substitute_windows(&template.windows, &values))
+                .env(env)
+                .hooks(template.hooks)
+                .options(template.options)
+        }
+        None => builder,
+    };
+
+    Ok(builder.root_dir(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_form() {
+        let dirs = parse_directories(
+            "- name: Downloads
+  path: ~/Downloads/
+- name: Moxide
+  path: ~/SoftwareDevelopment/cli/moxide
+  template: Rust
+- ~/Pictures",
+        )
+        .unwrap();
+
+        assert_eq!(
+            dirs.get("Downloads"),
+            Some(&PathBuf::from("~/Downloads/").into())
+        );
+        assert_eq!(
+            dirs.get("Moxide"),
+            Some(&Directory {
+                path: PathBuf::from("~/SoftwareDevelopment/cli/moxide"),
+                template: Some("Rust".to_string()),
+            })
+        );
+        assert_eq!(
+            dirs.get("Pictures"),
+            Some(&PathBuf::from("~/Pictures").into())
+        );
+    }
+
+    #[test]
+    fn test_map_form() {
+        let dirs = parse_directories(
+            "# Comment
+Home: ~/
+Moxide:
+  path: ~/SoftwareDevelopment/cli/moxide
+  template: Rust",
+        )
+        .unwrap();
+
+        assert_eq!(dirs.get("Home"), Some(&PathBuf::from("~/").into()));
+        assert_eq!(
+            dirs.get("Moxide").and_then(|dir| dir.template.as_deref()),
+            Some("Rust")
+        );
+    }
+
+    #[test]
+    fn test_legacy_form() {
+        let dirs = parse_directories(
+            "# Comment
+Home: ~/
+~/Downloads
+
+/tmp",
+        )
+        .unwrap();
+
+        assert_eq!(dirs.get("Home"), Some(&PathBuf::from("~/").into()));
+        assert_eq!(
+            dirs.get("Downloads"),
+            Some(&PathBuf::from("~/Downloads").into())
+        );
+        assert_eq!(dirs.get("tmp"), Some(&PathBuf::from("/tmp").into()));
+        assert!(parse_directories("").unwrap().names().next().is_none());
+    }
+
+    #[test]
+    fn test_legacy_mapping() {
+        let dirs = parse_directories(
+            "Work: ~/a: b
+Notes: ~/notes #1
+Drafts: [draft
+Stars: *stars",
+        )
+        .unwrap();
+
+        assert_eq!(dirs.get("Work"), Some(&PathBuf::from("~/a: b").into()));
+        assert_eq!(dirs.get("Notes"), Some(&PathBuf::from("~/notes #1").into()));
+        assert_eq!(dirs.get("Drafts"), Some(&PathBuf::from("[draft").into()));
+        assert_eq!(dirs.get("Stars"), Some(&PathBuf::from("*stars").into()));
+
+        let dirs = parse_directories("Notes: ~/notes #1\nHome: ~/").unwrap();
+        assert_eq!(dirs.get("Notes"), Some(&PathBuf::from("~/notes #1").into()));
+        // Nested mappings aren't legacy files, so their errors are reported
+        assert!(parse_directories("Home:\n  pth: ~/").is_err());
+    }
+
+    #[test]
+    fn test_scalar_paths() {
+        let dirs = parse_directories("Home: ~\nWork: 2024\nOld: -1\nDocs: 1.5").unwrap();
+        assert_eq!(dirs.get("Home"), Some(&PathBuf::from("~").into()));
+        assert_eq!(dirs.get("Work"), Some(&PathBuf::from("2024").into()));
+        assert_eq!(dirs.get("Old"), Some(&PathBuf::from("-1").into()));
+        assert_eq!(dirs.get("Docs"), Some(&PathBuf::from("1.5").into()));
+
+        let dirs = parse_directories("- ~\n- 2024").unwrap();
+        assert_eq!(dirs.get("~"), Some(&PathBuf::from("~").into()));
+        assert_eq!(dirs.get("2024"), Some(&PathBuf::from("2024").into()));
+    }
+
+    #[test]
+    fn test_add_directory() {
+        let path = Path::new("/code/moxide");
+        assert_eq!(
+            add_directory("", "moxide", path).unwrap(),
+            "- name: moxide\n  path: /code/moxide\n"
+        );
+        assert_eq!(
+            add_directory("# Dirs\n- ~/Pictures", "moxide", path).unwrap(),
+            "# Dirs\n- ~/Pictures\n- name: moxide\n  path: /code/moxide\n"
+        );
+        assert_eq!(
+            add_directory("Home: ~/\n", "Code: Moxide", path).unwrap(),
+            "Home: ~/\n'Code: Moxide': /code/moxide\n"
+        );
+        assert_eq!(
+            add_directory("~/Downloads\n", "moxide", path).unwrap(),
+            "~/Downloads\nmoxide: /code/moxide\n"
+        );
+
+        assert_eq!(
+            add_directory("Home: ~/\n", "moxide", Path::new("/code/a\nb")).unwrap(),
+            "Home: ~/\nmoxide: \"/code/a\\nb\"\n"
+        );
+        for (name, path) in [("a: b", "/x"), ("#a", "/x"), ("a", "/x\nb: /y")] {
+            let err = add_directory("~/Downloads\n", name, Path::new(path)).unwrap_err();
+            assert!(matches!(err, ParseDirectoryError::LegacyEntry { .. }));
+        }
+
+        let err = add_directory("- /code/moxide\n", "moxide", Path::new("/tmp")).unwrap_err();
+        assert!(matches!(err, ParseDirectoryError::DuplicateName { name, .. } if name == "moxide"));
+    }
+
+    #[test]
+    fn test_remove_directory() {
+        let list = "# Dirs
+- name: Home
+  path: ~/
+# Code
+- name: Moxide
+  path: ~/code/moxide
+  template: Rust
+- ~/Pictures
+";
+        assert_eq!(
+            remove_directory(list, "Moxide").unwrap().unwrap(),
+            "# Dirs\n- name: Home\n  path: ~/\n# Code\n- ~/Pictures\n"
+        );
+        assert_eq!(
+            remove_directory(list, "Pictures").unwrap().unwrap(),
+            "# Dirs\n- name: Home\n  path: ~/\n# Code\n- name: Moxide\n  path: ~/code/moxide\n  template: Rust\n"
+        );
+        assert_eq!(remove_directory(list, "Downloads").unwrap(), None);
+
+        let map = "Home: ~/\nMoxide:\n  path: ~/code/moxide\n";
+        assert_eq!(
+            remove_directory(map, "Moxide").unwrap().unwrap(),
+            "Home: ~/\n"
+        );
+        assert_eq!(
+            remove_directory("~/Downloads\n/tmp\n", "Downloads")
+                .unwrap()
+                .unwrap(),
+            "/tmp\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_flow_style() {
+        for content in ["[~/a, ~/b]\n", "{a: ~/a, b: ~/b}\n"] {
+            let err = add_directory(content, "c", Path::new("/c")).unwrap_err();
+            assert!(matches!(err, ParseDirectoryError::FlowStyle));
+            let err = remove_directory(content, "a").unwrap_err();
+            assert!(matches!(err, ParseDirectoryError::FlowStyle));
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let err =
+            parse_directories("- name: Home\n  path: ~/\n- name: Home\n  path: /home").unwrap_err();
+        assert!(matches!(err, ParseDirectoryError::DuplicateName { name, .. } if name == "Home"));
+
+        let err = parse_directories("- name: Home\n  pth: ~/").unwrap_err();
+        let ParseDirectoryError::Yaml(err) = err else {
+            panic!("expected a yaml error");
+        };
+        assert_eq!(err.location().map(|loc| loc.line()), Some(2));
+    }
+}
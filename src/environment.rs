@@ -0,0 +1,86 @@
+use crate::{error::MoxideError, helpers::absolute_path_from};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Parses the `KEY=VALUE` lines of a `.env` file, comments, empty lines and an `export` prefix
+/// are ignored
+pub fn parse_env_file(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let unquoted = ['"', '\'']
+                .into_iter()
+                .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote))
+                .unwrap_or(value);
+
+            Some((key.trim().to_string(), unquoted.to_string()))
+        })
+        .collect()
+}
+
+/// Merges two sets of variables, the variables of `overlay` take precedence
+pub fn merge_env(
+    base: Option<BTreeMap<String, String>>,
+    overlay: Option<BTreeMap<String, String>>,
+) -> Option<BTreeMap<String, String>> {
+    match (base, overlay) {
+        (Some(mut base), Some(overlay)) => {
+            base.extend(overlay);
+            Some(base)
+        }
+        (base, overlay) => overlay.or(base),
+    }
+}
+
+/// Reads `env_file` relative to `root` and merges it with `env`, the variables of `env` take
+/// precedence
+pub fn load_env(
+    env: Option<BTreeMap<String, String>>,
+    env_file: Option<&Path>,
+    root: Option<&Path>,
+) -> Result<Option<BTreeMap<String, String>>, MoxideError> {
+    let from_file = env_file
+        .map(|env_file| {
+            absolute_path_from(root, env_file)
+                .and_then(fs::read_to_string)
+                .map(|content| parse_env_file(&content))
+                .map_err(MoxideError::io(env_file))
+        })
+        .transpose()?;
+
+    Ok(merge_env(from_file, env))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let env = parse_env_file(
+            "# Database
+DATABASE_URL=postgres://localhost/db
+export PORT=3000
+
+NAME=\"Moxide Dev\"
+GREETING='hello world'",
+        );
+
+        assert_eq!(
+            env,
+            BTreeMap::from([
+                (
+                    "DATABASE_URL".to_string(),
+                    "postgres://localhost/db".to_string()
+                ),
+                ("PORT".to_string(), "3000".to_string()),
+                ("NAME".to_string(), "Moxide Dev".to_string()),
+                ("GREETING".to_string(), "hello world".to_string()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{directories::ParseDirectoryError, formats::FormatError};
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MoxideError {
+    #[error("Could not find a config directory")]
+    NoConfigDir,
+    #[error("Can't access {}: {source}", .path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Can't parse {}: {source}", .path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: FormatError,
+    },
+    #[error("{} has no supported file extension, use yaml, yml, toml or json", .0.display())]
+    UnsupportedFormat(PathBuf),
+    #[error(transparent)]
+    Directories(#[from] ParseDirectoryError),
+    #[error("The templates extend each other in a cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    #[error("{kind} {name} could not be found{}", did_you_mean(.suggestions))]
+    NotFound {
+        kind: &'static str,
+        name: String,
+        suggestions: Vec<String>,
+    },
+    #[error("The directory {} doesn't exist", .0.display())]
+    MissingDirectory(PathBuf),
+    #[error("No value for the variable {0} was provided, use --var {0}=VALUE")]
+    MissingVariable(String),
+    #[error("Can't read the input: {0}")]
+    Input(#[source] io::Error),
+    #[error("The on_create hook `{hook}` {reason}")]
+    Hook { hook: String, reason: String },
+    #[error("{0}")]
+    Tmux(String),
+    #[error("A tmux session with the name {0} already exists")]
+    SessionExists(String),
+    #[error("{} isn't trusted, review it and trust it with `moxide trust`", .0.display())]
+    Untrusted(PathBuf),
+    #[error("Aborted")]
+    Aborted,
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(", did you mean {}?", suggestions.join(" or "))
+    }
+}
+
+impl MoxideError {
+    pub fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
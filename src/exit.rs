@@ -0,0 +1,76 @@
+use moxide::MoxideError;
+
+#[macro_export]
+macro_rules! exit {
+    ($code:expr, $fmt:expr $(, $args:expr)*) => {{
+        eprintln!($fmt $(, $args)*);
+        std::process::exit($code as i32);
+    }};
+}
+
+/// The exit codes of moxide, `2` is used by clap for invalid arguments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Failure = 1,
+    NotFound = 3,
+    InvalidConfig = 4,
+    Tmux = 5,
+    SessionExists = 6,
+    Aborted = 130,
+}
+
+pub const EXIT_CODES_HELP: &str = "Exit codes:
+  0    Success
+  1    Any other failure
+  2    Invalid arguments
+  3    The project, template or directory could not be found
+  4    The configuration is invalid or a configured directory doesn't exist
+  5    Tmux is missing or failed
+  6    The tmux session already exists
+  130  Aborted by the user";
+
+impl From<&MoxideError> for ExitCode {
+    fn from(err: &MoxideError) -> Self {
+        match err {
+            MoxideError::NotFound { .. } => Self::NotFound,
+            MoxideError::NoConfigDir
+            | MoxideError::Io { .. }
+            | MoxideError::Parse { .. }
+            | MoxideError::UnsupportedFormat(_)
+            | MoxideError::Directories(_)
+            | MoxideError::Cycle(_)
+            | MoxideError::MissingDirectory(_) => Self::InvalidConfig,
+            MoxideError::Tmux(_) => Self::Tmux,
+            MoxideError::SessionExists(_) => Self::SessionExists,
+            MoxideError::Aborted => Self::Aborted,
+            MoxideError::MissingVariable(_)
+            | MoxideError::Input(_)
+            | MoxideError::Hook { .. }
+            | MoxideError::Untrusted(_) => Self::Failure,
+        }
+    }
+}
+
+pub trait Exit<T> {
+    fn exit<M: std::fmt::Display>(self, code: ExitCode, msg: M) -> T;
+}
+pub trait ExitErr<T> {
+    /// Exits with the code of the error category
+    fn exit_err(self) -> T;
+}
+
+impl<T, E> Exit<T> for Result<T, E> {
+    fn exit<M: std::fmt::Display>(self, code: ExitCode, msg: M) -> T {
+        self.unwrap_or_else(|_| exit!(code, "{}", msg))
+    }
+}
+impl<T> ExitErr<T> for Result<T, MoxideError> {
+    fn exit_err(self) -> T {
+        self.unwrap_or_else(|err| exit!(ExitCode::from(&err), "{}", err))
+    }
+}
+impl<T> Exit<T> for Option<T> {
+    fn exit<M: std::fmt::Display>(self, code: ExitCode, msg: M) -> T {
+        self.unwrap_or_else(|| exit!(code, "{}", msg))
+    }
+}
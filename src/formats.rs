@@ -0,0 +1,154 @@
+use crate::error::MoxideError;
+use clap::ValueEnum;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// The file formats of projects and templates
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    #[default]
+    Yaml,
+    Toml,
+    Json,
+}
+
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    TomlDe(#[from] toml::de::Error),
+    #[error(transparent)]
+    TomlSer(#[from] toml::ser::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl Format {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T, FormatError> {
+        Ok(match self {
+            Self::Yaml => serde_yaml::from_str(content)?,
+            Self::Toml => toml::from_str(content)?,
+            Self::Json => serde_json::from_str(content)?,
+        })
+    }
+
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, FormatError> {
+        Ok(match self {
+            Self::Yaml => serde_yaml::to_string(value)?,
+            Self::Toml => toml::to_string_pretty(value)?,
+            Self::Json => serde_json::to_string_pretty(value)? + "\n",
+        })
+    }
+}
+
+/// Reads a config file in the format of its extension
+pub fn read_config_file<T: DeserializeOwned>(path: &Path) -> Result<T, MoxideError> {
+    let format = Format::from_path(path)
+        .ok_or_else(|| MoxideError::UnsupportedFormat(path.to_path_buf()))?;
+    let content = fs::read_to_string(path).map_err(MoxideError::io(path))?;
+
+    format.parse(&content).map_err(|source| MoxideError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// All files of a config directory, sorted by their path
+pub fn config_files(dir: &Path) -> Result<Vec<PathBuf>, MoxideError> {
+    let entries = fs::read_dir(dir).map_err(MoxideError::io(dir))?;
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::{Project, ProjectSetup};
+
+    #[test]
+    fn test_formats_agree() {
+        let yaml = Format::Yaml
+            .parse::<Project>(
+                "name: Moxide
+root_dir: ~/moxide
+windows:
+  - name: Editor
+    panes:
+      - nvim",
+            )
+            .unwrap();
+        let toml = Format::Toml
+            .parse::<Project>(
+                "name = \"Moxide\"
+root_dir = \"~/moxide\"
+
+[[windows]]
+name = \"Editor\"
+panes = [\"nvim\"]",
+            )
+            .unwrap();
+        let json = Format::Json
+            .parse::<Project>(
+                r#"{"name": "Moxide", "root_dir": "~/moxide",
+                "windows": [{"name": "Editor", "panes": ["nvim"]}]}"#,
+            )
+            .unwrap();
+
+        assert_eq!(yaml, toml);
+        assert_eq!(yaml, json);
+        assert!(matches!(yaml.setup, ProjectSetup::Windows { .. }));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let project = Format::Yaml
+            .parse::<Project>(
+                "name: Moxide
+root_dir: ~/moxide
+windows:
+  - name: Editor
+    layout: tiled
+    panes:
+      - nvim
+      - dir: docs
+        focus: true",
+            )
+            .unwrap();
+
+        for format in [Format::Yaml, Format::Toml, Format::Json] {
+            let serialized = format.serialize(&project).unwrap();
+            assert_eq!(format.parse::<Project>(&serialized).unwrap(), project);
+        }
+    }
+}
@@ -1,12 +1,12 @@
-use crate::exit;
+use crate::error::MoxideError;
 use std::{
     env, fs,
     path::{Path, PathBuf},
 };
 
-pub fn get_config_dir() -> PathBuf {
+pub fn get_config_dir() -> Result<PathBuf, MoxideError> {
     if let Ok(dir) = env::var("MOXIDE_CONFIG") {
-        return PathBuf::from(dir);
+        return Ok(PathBuf::from(dir));
     }
 
     env::var("XDG_CONFIG_HOME")
@@ -14,8 +14,13 @@ pub fn get_config_dir() -> PathBuf {
         .map(PathBuf::from)
         .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
         .or_else(dirs::config_dir)
-        .exit(1, "Could not find a config directory")
-        .join("moxide")
+        .map(|dir| dir.join("moxide"))
+        .ok_or(MoxideError::NoConfigDir)
+}
+
+/// The directory for caches, they can be deleted at any time
+pub fn get_cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("moxide"))
 }
 
 fn expand_tilde<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
@@ -44,26 +49,13 @@ pub fn absolute_path(path: &Path) -> std::io::Result<PathBuf> {
     fs::canonicalize(expanded)
 }
 
-pub trait Exit<T> {
-    fn exit<M: std::fmt::Display>(self, code: i32, msg: M) -> T;
-}
-pub trait ExitErr<T> {
-    fn exit_err(self, code: i32) -> T;
-}
-
-impl<T, E> Exit<T> for Result<T, E> {
-    fn exit<M: std::fmt::Display>(self, code: i32, msg: M) -> T {
-        self.unwrap_or_else(|_| exit!(code, "{}", msg))
-    }
-}
-impl<T, E: std::error::Error> ExitErr<T> for Result<T, E> {
-    fn exit_err(self, code: i32) -> T {
-        self.unwrap_or_else(|err| exit!(code, "{}", err))
-    }
-}
-impl<T> Exit<T> for Option<T> {
-    fn exit<M: std::fmt::Display>(self, code: i32, msg: M) -> T {
-        self.unwrap_or_else(|| exit!(code, "{}", msg))
+/// Resolves `path` relative to `root` unless it's absolute or starts with a tilde
+pub fn absolute_path_from(root: Option<&Path>, path: &Path) -> std::io::Result<PathBuf> {
+    match root {
+        Some(root) if path.is_relative() && !path.starts_with("~") => {
+            absolute_path(&root.join(path))
+        }
+        _ => absolute_path(path),
     }
 }
 
@@ -91,14 +83,6 @@ pub fn apply_if_some<B, D, F: Fn(B, D) -> B>(base: B, data: Option<D>, predicate
     }
 }
 
-#[macro_export]
-macro_rules! exit {
-    ($code:expr, $fmt:expr $(, $args:expr)*) => {{
-        eprintln!($fmt $(, $args)*);
-        std::process::exit($code);
-    }};
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,289 @@
+use crate::{error::MoxideError, tmux};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+    process::Command,
+};
+use tmux_interface::{SetHook, Tmux};
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    /// Commands that run in the root directory before the session is created
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(with = "OneOrMany")]
+    pub on_create: Vec<String>,
+    /// Commands that run when a client attaches to the session
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(with = "OneOrMany")]
+    pub on_attach: Vec<String>,
+    /// Commands that run when a client detaches from the session
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(with = "OneOrMany")]
+    pub on_detach: Vec<String>,
+    /// Commands that run after the session was closed
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(with = "OneOrMany")]
+    pub on_stop: Vec<String>,
+}
+
+impl Hooks {
+    /// Runs the hooks of `self` first and the hooks of `other` afterwards
+    pub fn chain(mut self, other: Self) -> Self {
+        self.on_create.extend(other.on_create);
+        self.on_attach.extend(other.on_attach);
+        self.on_detach.extend(other.on_detach);
+        self.on_stop.extend(other.on_stop);
+        self
+    }
+}
+
+/// Chains two optional sets of hooks, the hooks of `base` run first
+pub fn chain_hooks(base: Option<Hooks>, other: Option<Hooks>) -> Option<Hooks> {
+    match (base, other) {
+        (Some(base), Some(other)) => Some(base.chain(other)),
+        (base, other) => base.or(other),
+    }
+}
+
+/// A single command or a list of commands
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(command) => vec![command],
+        OneOrMany::Many(commands) => commands,
+    })
+}
+
+/// Runs the `on_create` hooks one after another and stops at the first one that fails
+pub fn run_create_hooks(
+    hooks: Option<&Hooks>,
+    dir: Option<&Path>,
+    env: Option<&BTreeMap<String, String>>,
+) -> Result<(), MoxideError> {
+    for hook in hooks.into_iter().flat_map(|hooks| &hooks.on_create) {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(hook).envs(env.into_iter().flatten());
+        if let Some(dir) = dir {
+            cmd.current_dir(dir);
+        }
+
+        let reason = match cmd.status() {
+            Ok(status) if status.success() => continue,
+            Ok(status) => format!("failed with {status}"),
+            Err(err) => format!("could not be run: {err}"),
+        };
+        return Err(MoxideError::Hook {
+            hook: hook.clone(),
+            reason,
+        });
+    }
+
+    Ok(())
+}
+
+/// Escapes the characters that end an argument of a tmux format like `#{==:a,b}`
+fn format_literal(value: &str) -> String {
+    value
+        .replace('#', "##")
+        .replace(',', "#,")
+        .replace('}', "#}")
+}
+
+/// Quotes a string for the tmux command parser and the shell, both treat single quotes the same
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn run_shell(command: &str, dir: Option<&Path>) -> String {
+    match dir {
+        Some(dir) => {
+            let command = format!("cd {} && {command}", quote(&dir.to_string_lossy()));
+            format!("run-shell {}", quote(&command))
+        }
+        None => format!("run-shell {}", quote(command)),
+    }
+}
+
+/// Registers the attach, detach and stop hooks of a session
+pub fn apply_hooks<'a>(
+    tmux: Tmux<'a>,
+    session_name: &str,
+    hooks: Option<&Hooks>,
+    dir: Option<&Path>,
+) -> Tmux<'a> {
+    let Some(hooks) = hooks else {
+        return tmux;
+    };
+
+    let session_hooks = [
+        ("client-attached", &hooks.on_attach),
+        ("client-detached", &hooks.on_detach),
+    ];
+    let tmux = session_hooks
+        .into_iter()
+        .flat_map(|(name, commands)| commands.iter().map(move |command| (name, command)))
+        .fold(tmux, |tmux, (name, command)| {
+            tmux.add_command(
+                SetHook::new()
+                    .append()
+                    .target_session(session_name.to_string())
+                    .hook_name(name)
+                    .command(run_shell(command, dir))
+                    .build(),
+            )
+        });
+
+    if hooks.on_stop.is_empty() {
+        return tmux;
+    }
+
+    let (hook_name, command) = stop_hook(
+        session_name,
+        &hooks.on_stop,
+        dir,
+        &tmux::global_hooks("session-closed"),
+    );
+    tmux.add_command(
+        SetHook::new()
+            .global()
+            .hook_name(hook_name)
+            .command(command)
+            .build(),
+    )
+}
+
+/// The name and command of the global hook that runs the `on_stop` hooks of a session.
+///
+/// The hooks of a session are gone once it's closed, so a global hook that only runs for this
+/// session and removes itself afterwards is used. A hook that's left for a session with the same
+/// name, like the one of a restarted session, is replaced instead of running twice
+fn stop_hook(
+    session_name: &str,
+    on_stop: &[String],
+    dir: Option<&Path>,
+    existing: &HashMap<usize, String>,
+) -> (String, String) {
+    let filter = format!(
+        "#{{==:#{{hook_session_name}},{}}}",
+        format_literal(session_name)
+    );
+
+    let reused = existing
+        .iter()
+        .filter(|(_, command)| command.contains(&filter))
+        .map(|(index, _)| *index)
+        .min();
+    let index = reused.unwrap_or_else(|| {
+        let mut hasher = DefaultHasher::new();
+        session_name.hash(&mut hasher);
+        let mut index = 1000 + (hasher.finish() % 1_000_000) as usize;
+        while existing.contains_key(&index) {
+            index += 1;
+        }
+        index
+    });
+    let hook_name = format!("session-closed[{index}]");
+
+    let commands = on_stop
+        .iter()
+        .map(|command| run_shell(command, dir))
+        .chain([format!("set-hook -gu {hook_name}")])
+        .collect::<Vec<_>>()
+        .join(" ; ");
+    let command = format!("if-shell -F {} {}", quote(&filter), quote(&commands));
+    (hook_name, command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_hooks() {
+        let hooks = serde_yaml::from_str::<Hooks>(
+            "on_create: docker compose up -d
+on_stop:
+  - docker compose down
+  - echo stopped",
+        )
+        .unwrap();
+
+        assert_eq!(hooks.on_create, vec!["docker compose up -d".to_string()]);
+        assert_eq!(hooks.on_stop.len(), 2);
+        assert!(hooks.on_attach.is_empty());
+    }
+
+    #[test]
+    fn test_format_literal() {
+        assert_eq!(format_literal("moxide"), "moxide");
+        assert_eq!(format_literal("a,b}#c"), "a#,b#}##c");
+    }
+
+    #[test]
+    fn test_stop_hook() {
+        let on_stop = vec!["echo stopped".to_string()];
+        let (hook_name, command) = stop_hook("a,b", &on_stop, None, &HashMap::new());
+        assert!(hook_name.starts_with("session-closed["));
+        assert_eq!(
+            command,
+            format!(
+                "if-shell -F '#{{==:#{{hook_session_name}},a#,b}}' \
+                 'run-shell '\\''echo stopped'\\'' ; set-hook -gu {hook_name}'"
+            )
+        );
+
+        // The hook another session uses is skipped
+        let index: usize = hook_name["session-closed[".len()..hook_name.len() - 1]
+            .parse()
+            .unwrap();
+        let existing = HashMap::from([(
+            index,
+            "if-shell -F \"#{==:#{hook_session_name},a}\" \"run-shell 'echo a'\"".to_string(),
+        )]);
+        let (other_hook, _) = stop_hook("a,b", &on_stop, None, &existing);
+        assert_eq!(other_hook, format!("session-closed[{}]", index + 1));
+
+        // The hook of a restarted session is replaced, the way tmux shows it
+        let existing = HashMap::from([(
+            index + 5,
+            format!(
+                "if-shell -F \"#{{==:#{{hook_session_name}},a#,b}}\" \"run-shell 'echo stopped' ; set-hook -gu session-closed[{}]\"",
+                index + 5
+            ),
+        )]);
+        let (restarted_hook, restarted) = stop_hook("a,b", &on_stop, None, &existing);
+        assert_eq!(restarted_hook, format!("session-closed[{}]", index + 5));
+        assert!(restarted.ends_with(&format!("set-hook -gu session-closed[{}]'", index + 5)));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("echo 'hi'"), "'echo '\\''hi'\\'''");
+    }
+}
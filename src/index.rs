@@ -0,0 +1,144 @@
+use crate::{
+    error::MoxideError,
+    formats::{config_files, read_config_file},
+    helpers::get_cache_dir,
+    matching::resolve_name,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// A config file and the name of the project or template it defines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedFile {
+    pub path: PathBuf,
+    pub name: String,
+    pub hidden: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct CachedFile {
+    modified: SystemTime,
+    /// `None` if the file can't be parsed
+    name: Option<String>,
+    hidden: bool,
+}
+
+/// The cached files of every config directory
+type Cache = BTreeMap<PathBuf, BTreeMap<PathBuf, CachedFile>>;
+
+#[derive(Deserialize)]
+struct NameFields {
+    name: String,
+    #[serde(default)]
+    hidden: Option<bool>,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    Some(get_cache_dir()?.join("index.json"))
+}
+
+fn read_cache(path: &Path) -> Cache {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The cache only speeds up lookups, so failing to write it is ignored
+fn write_cache(path: &Path, cache: &Cache) {
+    let Ok(content) = serde_json::to_string(cache) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, content);
+}
+
+fn index_file(path: &Path, modified: SystemTime) -> CachedFile {
+    let fields = read_config_file::<NameFields>(path).ok();
+
+    CachedFile {
+        modified,
+        hidden: fields
+            .as_ref()
+            .and_then(|fields| fields.hidden)
+            .unwrap_or(false),
+        name: fields.map(|fields| fields.name),
+    }
+}
+
+/// The names defined by the config files of a directory. Only files that changed since they were
+/// cached are parsed, files that can't be parsed are skipped
+pub fn name_index(dir: &Path) -> Result<Vec<NamedFile>, MoxideError> {
+    let files = config_files(dir)?;
+    let cache_path = cache_path();
+    let mut cache = cache_path.as_deref().map(read_cache).unwrap_or_default();
+    let cached = cache.remove(dir).unwrap_or_default();
+
+    // Only changed, added or removed files make the cache outdated
+    let mut changed = files.len() != cached.len();
+    let indexed: BTreeMap<_, _> = files
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let file = match cached.get(&path) {
+                Some(file) if file.modified == modified && modified != SystemTime::UNIX_EPOCH => {
+                    file.clone()
+                }
+                cached_file => {
+                    let file = index_file(&path, modified);
+                    changed |= cached_file != Some(&file);
+                    file
+                }
+            };
+            (path, file)
+        })
+        .collect();
+
+    if let Some(cache_path) = cache_path.filter(|_| changed) {
+        cache.insert(dir.to_path_buf(), indexed.clone());
+        write_cache(&cache_path, &cache);
+    }
+
+    Ok(indexed
+        .into_iter()
+        .filter_map(|(path, file)| {
+            Some(NamedFile {
+                path,
+                name: file.name?,
+                hidden: file.hidden,
+            })
+        })
+        .collect())
+}
+
+/// Finds a config file in `dir` by its file name without the extension, files that can't be
+/// parsed are found as well
+pub fn find_by_file_stem(dir: &Path, name: &str) -> Result<Option<PathBuf>, MoxideError> {
+    let stems: Vec<_> = config_files(dir)?
+        .into_iter()
+        .filter_map(|path| Some((path.file_stem()?.to_str()?.to_string(), path)))
+        .collect();
+
+    Ok(resolve_name(name, stems, |(stem, _)| stem).map(|(_, path)| path))
+}
+
+/// Finds the config file in `dir` that defines `name`
+pub fn find_config_file<T: DeserializeOwned>(
+    dir: &Path,
+    name: &str,
+) -> Result<Option<T>, MoxideError> {
+    name_index(dir)?
+        .into_iter()
+        .find(|file| file.name == name)
+        .map(|file| read_config_file(&file.path))
+        .transpose()
+}
@@ -1,25 +1,28 @@
-use crate::helpers::get_config_dir;
+use crate::{error::MoxideError, helpers::get_config_dir};
 use std::{
-    fs, io,
+    fs,
     path::{Path, PathBuf},
 };
 
-fn create_config_dir() -> io::Result<PathBuf> {
-    let config_dir = get_config_dir();
-    fs::create_dir_all(&config_dir)?;
+fn create_config_dir() -> Result<PathBuf, MoxideError> {
+    let config_dir = get_config_dir()?;
+    fs::create_dir_all(&config_dir).map_err(MoxideError::io(&config_dir))?;
 
     Ok(config_dir)
 }
 
-fn create_config_files(config_path: &Path) -> io::Result<()> {
-    fs::create_dir(config_path.join("projects"))?;
-    fs::create_dir(config_path.join("templates"))?;
-    fs::File::create(config_path.join("directories.yaml"))?;
+fn create_config_files(config_path: &Path) -> Result<(), MoxideError> {
+    for dir in ["projects", "templates"] {
+        let path = config_path.join(dir);
+        fs::create_dir(&path).map_err(MoxideError::io(path))?;
+    }
+    let path = config_path.join("directories.yaml");
+    fs::File::create(&path).map_err(MoxideError::io(path))?;
 
     Ok(())
 }
 
-pub fn init_config() -> io::Result<PathBuf> {
+pub fn init_config() -> Result<PathBuf, MoxideError> {
     let config_path = create_config_dir()?;
     create_config_files(&config_path)?;
 
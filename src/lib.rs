@@ -0,0 +1,26 @@
+//! Moxide is a tmux session manager, projects, templates and directories are turned into tmux
+//! sessions
+pub mod directories;
+pub mod environment;
+pub mod error;
+pub mod formats;
+pub mod helpers;
+pub mod hooks;
+pub mod index;
+pub mod init;
+pub mod matching;
+pub mod projects;
+pub mod session;
+pub mod settings;
+pub mod templates;
+pub mod tmux;
+pub mod trust;
+pub mod variables;
+pub mod widgets;
+
+pub use directories::{parse_directory_config, Directories, Directory};
+pub use error::MoxideError;
+pub use projects::{find_project, parse_project_config, Project};
+pub use session::SessionBuilder;
+pub use settings::Settings;
+pub use templates::{find_template, parse_template_config, Template};
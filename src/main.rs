@@ -1,12 +1,7 @@
 mod cli;
 mod commands;
-mod directories;
-mod helpers;
-mod init;
-mod projects;
-mod templates;
-mod tmux;
-mod widgets;
+mod exit;
+mod prompt;
 
 use clap::Parser;
 
@@ -18,12 +13,26 @@ fn main() {
         cli::Commands::Directory { action } => commands::directory::directory_handler(action),
         cli::Commands::Template { action } => commands::template::template_handler(action),
         cli::Commands::Project { action } => commands::project::project_handler(action),
+        cli::Commands::Start(args) => commands::directory::start_handler(&args),
+        cli::Commands::Trust { path } => commands::trust::trust_handler(&path),
+        cli::Commands::Stop { name, all, timeout } => {
+            commands::stop::stop_handler(name.as_deref(), all, timeout);
+        }
+        cli::Commands::Restart {
+            name,
+            detached,
+            attach,
+        } => commands::restart::restart_handler(&name, detached, attach),
+        cli::Commands::Status => commands::status::status_handler(),
         cli::Commands::List(args) => commands::list::list_handler(args),
+        cli::Commands::Check => commands::check::check_handler(),
         cli::Commands::Freeze {
             name,
             force,
             file_name,
+            format,
             stdout,
-        } => commands::freeze::freeze_handler(name, force, file_name.as_deref(), stdout),
+        } => commands::freeze::freeze_handler(name, force, file_name.as_deref(), format, stdout),
+        cli::Commands::Schema { kind } => commands::schema::schema_handler(kind),
     }
 }
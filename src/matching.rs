@@ -0,0 +1,144 @@
+use crate::{
+    directories::parse_directory_config, error::MoxideError, projects::project_files,
+    templates::template_files,
+};
+use itertools::Itertools;
+
+/// How many names are suggested at most
+const MAX_SUGGESTIONS: usize = 3;
+
+fn unique_position<T>(items: &[T], predicate: impl Fn(&T) -> bool) -> Option<usize> {
+    items.iter().positions(predicate).exactly_one().ok()
+}
+
+/// Finds the item the user meant, an exact match wins over a case-insensitive match which wins
+/// over a unique case-insensitive prefix
+pub fn resolve_name<T>(query: &str, items: Vec<T>, name: impl Fn(&T) -> &str) -> Option<T> {
+    let query_lower = query.to_lowercase();
+    let position = items
+        .iter()
+        .position(|item| name(item) == query)
+        .or_else(|| unique_position(&items, |item| name(item).to_lowercase() == query_lower))
+        .or_else(|| {
+            unique_position(&items, |item| {
+                name(item).to_lowercase().starts_with(&query_lower)
+            })
+        })?;
+
+    items.into_iter().nth(position)
+}
+
+/// Like `resolve_name`, but names that lead to the same target count as a single match
+pub fn resolve_target<T: PartialEq + Clone>(query: &str, items: &[(String, T)]) -> Option<T> {
+    let query_lower = query.to_lowercase();
+    let unique_target = |is_match: &dyn Fn(&str) -> bool| {
+        let mut targets: Vec<&T> = Vec::new();
+        for (_, target) in items.iter().filter(|(name, _)| is_match(name)) {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        targets.into_iter().exactly_one().ok().cloned()
+    };
+
+    items
+        .iter()
+        .find(|(name, _)| name == query)
+        .map(|(_, target)| target.clone())
+        .or_else(|| unique_target(&|name| name.to_lowercase() == query_lower))
+        .or_else(|| unique_target(&|name| name.to_lowercase().starts_with(&query_lower)))
+}
+
+/// The names that are similar to the query, ranked by their edit distance
+pub fn similar_names<'a, T>(query: &str, items: &'a [T], name: impl Fn(&T) -> &str) -> Vec<&'a T> {
+    let query = query.to_lowercase();
+    let max_distance = 1 + query.chars().count() / 4;
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let candidate = name(item).to_lowercase();
+            let distance = strsim::damerau_levenshtein(&query, &candidate);
+            let is_similar = distance <= max_distance || candidate.contains(&query);
+            // Names that start with the query are ranked first among names with the same distance
+            is_similar.then_some(((distance, !candidate.starts_with(&query)), item))
+        })
+        .sorted_by_key(|(rank, _)| *rank)
+        .map(|(_, item)| item)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// Suggestions like `project OsmApp` out of all projects, templates and directories
+fn suggestions(query: &str) -> Vec<String> {
+    let projects = project_files()
+        .into_iter()
+        .flatten()
+        .map(|file| ("project", file.name));
+    let templates = template_files()
+        .into_iter()
+        .flatten()
+        .map(|file| ("template", file.name));
+    let directories = parse_directory_config()
+        .into_iter()
+        .flat_map(|dirs| dirs.into_iter().map(|(name, _)| ("directory", name)));
+
+    let candidates: Vec<_> = projects.chain(templates).chain(directories).collect();
+    similar_names(query, &candidates, |(_, name)| name)
+        .into_iter()
+        .map(|(kind, name)| format!("{kind} {name}"))
+        .collect()
+}
+
+/// A `NotFound` error with suggestions of similar names
+pub fn not_found(kind: &'static str, name: &str) -> MoxideError {
+    MoxideError::NotFound {
+        kind,
+        name: name.to_string(),
+        suggestions: suggestions(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 4] = ["OsmApp", "moxide", "Moxide-Web", "Rust"];
+
+    fn resolve(query: &str) -> Option<&'static str> {
+        resolve_name(query, NAMES.to_vec(), |name| name)
+    }
+
+    #[test]
+    fn test_resolve_name() {
+        assert_eq!(resolve("moxide"), Some("moxide"));
+        assert_eq!(resolve("rust"), Some("Rust"));
+        assert_eq!(resolve("osm"), Some("OsmApp"));
+        assert_eq!(resolve("mox"), None);
+        assert_eq!(resolve("python"), None);
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let items = [
+            ("Shop_app".to_string(), "Shop_app"),
+            ("Shop.app".to_string(), "Shop_app"),
+            ("Shopping".to_string(), "Shopping"),
+        ];
+        assert_eq!(resolve_target("shop.", &items), Some("Shop_app"));
+        assert_eq!(resolve_target("shop_", &items), Some("Shop_app"));
+        assert_eq!(resolve_target("shopp", &items), Some("Shopping"));
+        assert_eq!(resolve_target("shop", &items), None);
+        assert_eq!(resolve_target("shop", &items[..2]), Some("Shop_app"));
+    }
+
+    #[test]
+    fn test_similar_names() {
+        assert_eq!(similar_names("rsut", &NAMES, |name| name), vec![&"Rust"]);
+        assert_eq!(
+            similar_names("mox", &NAMES, |name| name),
+            vec![&"moxide", &"Moxide-Web"]
+        );
+        assert!(similar_names("python", &NAMES, |name| name).is_empty());
+    }
+}
@@ -1,48 +1,157 @@
 use crate::{
-    exit,
-    helpers::{get_config_dir, Exit},
-    templates::{find_template, Window},
+    environment::{load_env, merge_env},
+    error::MoxideError,
+    formats::{config_files, read_config_file},
+    helpers::{absolute_path, dir_name, get_config_dir},
+    hooks::{chain_hooks, Hooks},
+    index::{find_by_file_stem, name_index, NamedFile},
+    matching::{not_found, resolve_name},
+    session::SessionBuilder,
+    settings::Settings,
+    templates::{find_template, Template, Window},
+    tmux::OriginKind,
+    variables::{deserialize_values, resolve_variables, substitute_windows, Scalar, Variable},
     widgets::table::Table,
 };
-use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{ser::SerializeMap, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// The repository-local project files, in the order they are looked for
+const LOCAL_PROJECT_FILES: [&str; 2] = [".moxide.yaml", ".moxide/project.yaml"];
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, JsonSchema, Debug, PartialEq, Eq)]
 pub struct Project {
     pub name: String,
     pub root_dir: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<HashMap<String, Scalar>>")]
+    pub vars: Option<HashMap<String, String>>,
+    /// Environment variables of the whole session, they take precedence over the template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<BTreeMap<String, Scalar>>")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// A `.env` file relative to the root directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+    /// Hooks that run after the hooks of the template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Hooks>,
+    /// Tmux options of the session, they take precedence over the template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<BTreeMap<String, Scalar>>")]
+    pub options: Option<BTreeMap<String, String>>,
     #[serde(flatten)]
     pub setup: ProjectSetup,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+impl Project {
+    /// The session of the project defined in `config_path`, `vars` take precedence over the
+    /// variables of the project
+    pub fn session(
+        self,
+        config_path: Option<PathBuf>,
+        settings: &Settings,
+        vars: Vec<(String, String)>,
+        prompt: impl FnMut(&str, &Variable) -> Result<String, MoxideError>,
+    ) -> Result<SessionBuilder, MoxideError> {
+        let path = absolute_path(&self.root_dir)
+            .map_err(|_| MoxideError::MissingDirectory(self.root_dir.clone()))?;
+        let template = self.setup.into_template()?;
+
+        let mut values = self.vars.unwrap_or_default();
+        values.extend(vars);
+        let values = resolve_variables(template.variables.as_ref(), values, prompt)?;
+
+        let template_env = load_env(template.env, template.env_file.as_deref(), Some(&path))?;
+        let project_env = load_env(self.env, self.env_file.as_deref(), Some(&path))?;
+
+        Ok(SessionBuilder::new(settings.session_name(&self.name))
+            .origin(OriginKind::Project, &self.name, config_path)
+            .root_dir(path)
+            .windows(substitute_windows(&template.windows, &values))
+            .env(merge_env(template_env, project_env))
+            .hooks(chain_hooks(template.hooks, self.hooks))
+            .options(merge_env(template.options, self.options)))
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum ProjectSetup {
     Template(String),
     Windows { windows: Vec<Window> },
 }
 
-impl From<ProjectSetup> for Vec<Window> {
-    fn from(val: ProjectSetup) -> Self {
-        match val {
-            ProjectSetup::Template(template_name) => {
-                let template = find_template(&template_name)
-                    .unwrap_or_else(|| exit!(1, "Template {} could not be found", template_name));
+impl ProjectSetup {
+    /// Resolves the template of the setup, windows of the project are treated like an unnamed
+    /// template
+    pub fn into_template(self) -> Result<Template, MoxideError> {
+        match self {
+            Self::Template(template_name) => find_template(&template_name),
+            Self::Windows { windows } => Ok(Template {
+                windows,
+                ..Default::default()
+            }),
+        }
+    }
+}
 
-                template.windows
-            }
-            ProjectSetup::Windows { windows } => windows,
+/// A project uses either a template or its own windows, see the `Deserialize` impl of `Project`
+impl JsonSchema for ProjectSetup {
+    fn schema_name() -> Cow<'static, str> {
+        "ProjectSetup".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "oneOf": [
+                {
+                    "properties": { "template": generator.subschema_for::<String>() },
+                    "required": ["template"],
+                },
+                {
+                    "properties": { "windows": generator.subschema_for::<Vec<Window>>() },
+                    "required": ["windows"],
+                },
+            ]
+        })
+    }
+}
+
+/// Serialized as the `template` or `windows` field of the flattened project
+impl Serialize for ProjectSetup {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        match self {
+            Self::Template(template_name) => map.serialize_entry("template", template_name)?,
+            Self::Windows { windows } => map.serialize_entry("windows", windows)?,
         }
+        map.end()
     }
 }
 
-impl From<ProjectSetup> for Table<String, String> {
-    fn from(value: ProjectSetup) -> Self {
+impl TryFrom<ProjectSetup> for Vec<Window> {
+    type Error = MoxideError;
+
+    fn try_from(val: ProjectSetup) -> Result<Self, Self::Error> {
+        Ok(val.into_template()?.windows)
+    }
+}
+
+impl TryFrom<ProjectSetup> for Table<String, String> {
+    type Error = MoxideError;
+
+    fn try_from(value: ProjectSetup) -> Result<Self, Self::Error> {
         let template_name = match &value {
             ProjectSetup::Template(template_name) => Some(template_name.clone()),
             ProjectSetup::Windows { .. } => None,
         };
-        let windows: Vec<Window> = value.into();
+        let windows: Vec<Window> = value.try_into()?;
         let windows: Vec<&Window> = windows.iter().collect();
 
         let mut rows = Self::new(vec![(
@@ -51,7 +160,7 @@ impl From<ProjectSetup> for Table<String, String> {
         )]);
         rows.extend_table(Self::from_iter(windows));
 
-        rows
+        Ok(rows)
     }
 }
 
@@ -64,6 +173,14 @@ impl<'de> Deserialize<'de> for Project {
         struct RawProject {
             name: String,
             root_dir: PathBuf,
+            #[serde(default, deserialize_with = "deserialize_values")]
+            vars: Option<HashMap<String, String>>,
+            #[serde(default, deserialize_with = "deserialize_values")]
+            env: Option<BTreeMap<String, String>>,
+            env_file: Option<PathBuf>,
+            hooks: Option<Hooks>,
+            #[serde(default, deserialize_with = "deserialize_values")]
+            options: Option<BTreeMap<String, String>>,
             template: Option<String>,
             windows: Option<Vec<Window>>,
         }
@@ -83,61 +200,83 @@ impl<'de> Deserialize<'de> for Project {
         Ok(Self {
             name: raw.name,
             root_dir: raw.root_dir,
+            vars: raw.vars,
+            env: raw.env,
+            env_file: raw.env_file,
+            hooks: raw.hooks,
+            options: raw.options,
             setup,
         })
     }
 }
 
-pub fn find_project(name: &str) -> Option<Project> {
-    let projects_dir = get_config_dir().join("projects/");
-    let file_path = projects_dir.join(format!("{name}.yaml"));
-    let is_valid_path = file_path.exists() && file_path.is_file();
-    let matching_path = is_valid_path.then_some(file_path);
+/// Finds a project by its name, or by a unique case-insensitive match or prefix of it
+pub fn find_project(name: &str) -> Result<Project, MoxideError> {
+    read_config_file(&find_project_path(name)?)
+}
 
-    let matching_project = matching_path.and_then(|path| {
-        let content = fs::read_to_string(&path).ok()?;
-        let project = serde_yaml::from_str::<Project>(&content).ok()?;
-        (project.name == name).then_some(project)
-    });
+/// Finds the file of a project the same way as `find_project`
+pub fn find_project_path(name: &str) -> Result<PathBuf, MoxideError> {
+    resolve_name(name, project_files()?, |file| &file.name)
+        .map(|file| file.path)
+        .ok_or_else(|| not_found("Project", name))
+}
 
-    if matching_project.is_some() {
-        return matching_project;
-    }
+/// Finds the file of a project like `find_project_path`, or by its file name if no project
+/// matches, so files with syntax errors can be found to fix them
+pub fn find_project_source(name: &str) -> Result<PathBuf, MoxideError> {
+    find_project_path(name)
+        .or_else(|err| find_by_file_stem(&get_config_dir()?.join("projects/"), name)?.ok_or(err))
+}
 
-    fs::read_dir(&projects_dir)
-        .exit(1, "Can't read template config")
-        .find_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-            if !path.is_file() {
-                return None;
-            }
+/// The names of all projects, without parsing files that didn't change since they were indexed
+pub fn project_files() -> Result<Vec<NamedFile>, MoxideError> {
+    name_index(&get_config_dir()?.join("projects/"))
+}
 
-            let content = fs::read_to_string(&path).ok()?;
-            let project = serde_yaml::from_str::<Project>(&content).ok()?;
-            (project.name == name).then_some(project)
-        })
+pub fn parse_project_config() -> Result<impl Iterator<Item = Project>, MoxideError> {
+    let files = config_files(&get_config_dir()?.join("projects/"))?;
+
+    Ok(files
+        .into_iter()
+        .filter_map(|path| read_config_file::<Project>(&path).ok()))
 }
 
-pub fn parse_project_config() -> impl Iterator<Item = Project> {
-    let projects_dir = get_config_dir().join("projects/");
-    let projects_content = fs::read_dir(&projects_dir).exit(1, "Can't read template config");
+/// Finds the repository-local project file of a directory
+pub fn find_local_project_file(dir: &Path) -> Option<PathBuf> {
+    LOCAL_PROJECT_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.is_file())
+}
 
-    projects_content.filter_map(|entry| {
-        let entry = entry.ok()?;
-        let path = entry.path();
-        if !path.is_file() {
-            return None;
-        }
+/// Reads a repository-local project, its `name` and `root_dir` default to `dir` and a relative
+/// `root_dir` is resolved from it
+pub fn read_local_project(file: &Path, dir: &Path) -> Result<Project, MoxideError> {
+    let mut raw: serde_yaml::Mapping = read_config_file(file)?;
+    for (key, default) in [
+        ("name", dir_name(dir)),
+        ("root_dir", dir.to_string_lossy().to_string()),
+    ] {
+        raw.entry(key.into()).or_insert(default.into());
+    }
+
+    let mut project: Project =
+        serde_yaml::from_value(raw.into()).map_err(|source| MoxideError::Parse {
+            path: file.to_path_buf(),
+            source: source.into(),
+        })?;
+    if project.root_dir.is_relative() && !project.root_dir.starts_with("~") {
+        project.root_dir = dir.join(&project.root_dir);
+    }
 
-        let content = fs::read_to_string(&path).ok()?;
-        serde_yaml::from_str::<Project>(&content).ok()
-    })
+    Ok(project)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::templates::{Pane, PaneConfig};
 
     #[test]
     fn test_parser() {
@@ -160,17 +299,30 @@ windows:
             Project {
                 name: "OsmApp".to_string(),
                 root_dir: PathBuf::from("~/GitHub/osmapp"),
+                vars: None,
+                env: None,
+                env_file: None,
+                hooks: None,
+                options: None,
                 setup: ProjectSetup::Windows {
                     windows: vec![
                         Window {
                             name: Some(" Neovim".to_string()),
-                            panes: vec!["nvim".to_string()],
+                            panes: vec![Pane::from("nvim")],
                             layout: None,
+                            dir: None,
+                            env: None,
+                            options: None,
+                            focus: None,
                         },
                         Window {
                             name: Some("Server".to_string()),
-                            panes: vec!["yarn run dev".to_string()],
+                            panes: vec![Pane::from("yarn run dev")],
                             layout: None,
+                            dir: None,
+                            env: None,
+                            options: None,
+                            focus: None,
                         }
                     ]
                 }
@@ -190,8 +342,102 @@ template: Svelte",
             Project {
                 name: "Dlool".to_string(),
                 root_dir: PathBuf::from("~/SoftwareDevelopment/web/Dlool/dlool_frontend_v2/"),
+                vars: None,
+                env: None,
+                env_file: None,
+                hooks: None,
+                options: None,
                 setup: ProjectSetup::Template("Svelte".to_string())
             }
         );
     }
+
+    #[test]
+    fn test_parse_vars() {
+        let project = serde_yaml::from_str::<Project>(
+            "name: Dlool
+root_dir: ~/code/dlool
+template: Svelte
+vars:
+  port: 5173
+  host: localhost",
+        )
+        .unwrap();
+
+        assert_eq!(
+            project.vars,
+            Some(HashMap::from([
+                ("port".to_string(), "5173".to_string()),
+                ("host".to_string(), "localhost".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_pane_dir() {
+        let project = serde_yaml::from_str::<Project>(
+            "name: OsmApp
+root_dir: ~/GitHub/osmapp/
+windows:
+  - dir: web
+    panes:
+      - command: yarn run dev
+        dir: server",
+        )
+        .unwrap();
+
+        let ProjectSetup::Windows { windows } = project.setup else {
+            panic!("expected windows");
+        };
+        assert_eq!(windows[0].dir, Some(PathBuf::from("web")));
+        assert_eq!(
+            windows[0].panes,
+            vec![Pane::Detailed(PaneConfig {
+                command: Some("yarn run dev".to_string()),
+                dir: Some(PathBuf::from("server")),
+                ..Default::default()
+            })]
+        );
+    }
+
+    #[test]
+    fn test_serialize_template_project() {
+        let project = Project {
+            name: "Dlool".to_string(),
+            root_dir: PathBuf::from("/code/dlool"),
+            vars: None,
+            env: None,
+            env_file: None,
+            hooks: None,
+            options: None,
+            setup: ProjectSetup::Template("Svelte".to_string()),
+        };
+
+        let yaml = serde_yaml::to_string(&project).unwrap();
+        assert_eq!(
+            yaml,
+            "name: Dlool\nroot_dir: /code/dlool\ntemplate: Svelte\n"
+        );
+        assert_eq!(serde_yaml::from_str::<Project>(&yaml).unwrap(), project);
+    }
+
+    #[test]
+    fn test_schema_setup() {
+        let schema = serde_json::to_value(schemars::schema_for!(Project)).unwrap();
+        let required: Vec<_> = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|setup| setup["required"].clone())
+            .collect();
+
+        assert_eq!(
+            required,
+            vec![
+                serde_json::json!(["template"]),
+                serde_json::json!(["windows"])
+            ]
+        );
+        assert_eq!(schema["required"], serde_json::json!(["name", "root_dir"]));
+    }
 }
@@ -0,0 +1,22 @@
+use moxide::{error::MoxideError, variables::Variable};
+use std::io::{self, IsTerminal, Write};
+
+/// Asks for the value of a variable on the terminal, without one the value is missing
+pub fn prompt_variable(name: &str, variable: &Variable) -> Result<String, MoxideError> {
+    if !io::stdin().is_terminal() {
+        return Err(MoxideError::MissingVariable(name.to_string()));
+    }
+
+    eprint!("{}: ", variable.prompt_text().unwrap_or(name));
+    let _ = io::stderr().flush();
+
+    let mut value = String::new();
+    let read = io::stdin()
+        .read_line(&mut value)
+        .map_err(MoxideError::Input)?;
+    if read == 0 {
+        return Err(MoxideError::Aborted);
+    }
+
+    Ok(value.trim_end_matches(['\n', '\r']).to_string())
+}
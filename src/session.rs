@@ -0,0 +1,163 @@
+use crate::{
+    error::MoxideError,
+    helpers::apply_if_some,
+    hooks::{apply_hooks, run_create_hooks, Hooks},
+    templates::{apply_options, apply_windows, push_env, Window},
+    tmux::{self, NameCollision, Origin, OriginKind, Sessions},
+};
+use std::{borrow::Cow, collections::BTreeMap, path::PathBuf};
+use tmux_interface::{NewSession, Tmux};
+
+/// Builds a new tmux session out of the windows, environment, hooks and options of a template
+#[derive(Debug, Default)]
+pub struct SessionBuilder {
+    name: String,
+    root_dir: Option<PathBuf>,
+    window_name: Option<String>,
+    windows: Vec<Window>,
+    env: Option<BTreeMap<String, String>>,
+    hooks: Option<Hooks>,
+    options: Option<BTreeMap<String, String>>,
+    attach: bool,
+    name_collision: NameCollision,
+    sessions: Option<Sessions>,
+    origin: Option<Origin>,
+}
+
+impl SessionBuilder {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The requested name of the session
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Replaces the requested name of the session
+    pub fn rename<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The directory the session is started in, relative directories of windows and panes are
+    /// resolved from it
+    pub fn root_dir(mut self, root_dir: PathBuf) -> Self {
+        self.root_dir = Some(root_dir);
+        self
+    }
+
+    /// The name of the first window, it's replaced by the name of the first template window
+    pub fn window_name<S: Into<String>>(mut self, window_name: S) -> Self {
+        self.window_name = Some(window_name.into());
+        self
+    }
+
+    pub fn windows(mut self, windows: Vec<Window>) -> Self {
+        self.windows = windows;
+        self
+    }
+
+    pub fn env(mut self, env: Option<BTreeMap<String, String>>) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn hooks(mut self, hooks: Option<Hooks>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub fn options(mut self, options: Option<BTreeMap<String, String>>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// How the session is named when a session with its name already exists
+    pub fn name_collision(mut self, name_collision: NameCollision) -> Self {
+        self.name_collision = name_collision;
+        self
+    }
+
+    /// The running sessions, they're listed when the session is started if they aren't set
+    pub fn sessions(mut self, sessions: Sessions) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// Stores where the session came from in the session, `moxide status` shows it and sessions
+    /// are recognized by it even if they were renamed
+    pub fn origin<S: Into<String>>(
+        mut self,
+        kind: OriginKind,
+        name: S,
+        config_path: Option<PathBuf>,
+    ) -> Self {
+        self.origin = Some(Origin {
+            kind,
+            name: name.into(),
+            root: None,
+            config_path,
+        });
+        self
+    }
+
+    /// Attaches to the session, or switches to it when run inside of tmux
+    pub fn attach(mut self, attach: bool) -> Self {
+        self.attach = attach;
+        self
+    }
+
+    /// Runs the `on_create` hooks and starts the session. Returns the name of the session, which
+    /// differs from the requested name if a session with that name already exists
+    pub fn start(self) -> Result<String, MoxideError> {
+        let root_dir = self.root_dir.as_ref();
+        run_create_hooks(
+            self.hooks.as_ref(),
+            root_dir.map(PathBuf::as_path),
+            self.env.as_ref(),
+        )?;
+
+        let sessions = match self.sessions {
+            Some(ref sessions) => Cow::Borrowed(sessions),
+            None => Cow::Owned(Sessions::fetch()?),
+        };
+        let name = sessions.unused_name(&self.name, self.name_collision)?;
+        let new_session = NewSession::new().detached().session_name(&name);
+        let new_session = apply_if_some(new_session, root_dir, |cmd, dir| {
+            cmd.start_directory(dir.to_string_lossy())
+        });
+        let new_session = apply_if_some(new_session, self.window_name.as_deref(), |cmd, name| {
+            cmd.window_name(name)
+        });
+        let mut new_session_cmd = new_session.build();
+        push_env(&mut new_session_cmd, self.env.as_ref());
+
+        let tmux = apply_hooks(
+            Tmux::new().add_command(new_session_cmd),
+            &name,
+            self.hooks.as_ref(),
+            root_dir.map(PathBuf::as_path),
+        );
+        let tmux = apply_options(tmux, &name, self.options.as_ref());
+        let origin = self.origin.map(|origin| Origin {
+            root: self.root_dir.clone(),
+            ..origin
+        });
+        let tmux = apply_if_some(tmux, origin.as_ref(), |tmux, origin| {
+            origin.apply(tmux, &name)
+        });
+        let tmux = apply_if_some(
+            tmux,
+            self.attach.then(|| tmux::attach(&name)),
+            |tmux, cmd| tmux.add_command(cmd),
+        );
+
+        tmux::run(apply_windows(tmux, &self.windows, root_dir)?)?;
+
+        Ok(name)
+    }
+}
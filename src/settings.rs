@@ -0,0 +1,109 @@
+use crate::{
+    error::MoxideError,
+    formats::read_config_file,
+    helpers::get_config_dir,
+    tmux::{normalize_name, NameCollision},
+    widgets::table::TableStyle,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Defaults of the command line, the flags of a command take precedence over them
+#[derive(Deserialize, JsonSchema, Debug, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Start sessions detached, `--attach` overrides it
+    pub detached: bool,
+    /// Start a new session even if the session already exists, `--reuse-session` overrides it
+    pub always_new_session: bool,
+    /// How a new session is named when a session with its name already exists
+    pub name_collision: NameCollision,
+    /// Replacements that are applied to session names, `.` and `:` are always replaced with `_`
+    pub name_replacements: BTreeMap<String, String>,
+    /// The template `dir start` uses for directories without a template
+    pub default_template: Option<String>,
+    /// The formatters of `moxide list`, `{}` is replaced with the name
+    pub list_format: ListFormat,
+    pub table_style: TableStyle,
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListFormat {
+    pub project: Option<String>,
+    pub template: Option<String>,
+    pub directory: Option<String>,
+}
+
+impl Settings {
+    pub fn path() -> Result<PathBuf, MoxideError> {
+        Ok(get_config_dir()?.join("config.yaml"))
+    }
+
+    /// Reads the settings, a missing file results in the default settings
+    pub fn load() -> Result<Self, MoxideError> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        read_config_file(&path)
+    }
+
+    /// The name of the tmux session for a project, template or directory
+    pub fn session_name(&self, name: &str) -> String {
+        normalize_name(name, &self.name_replacements)
+    }
+}
+
+/// Resolves a flag that can be turned on and off on the command line, `default` is used if
+/// neither happened
+pub const fn resolve_flag(on: bool, off: bool, default: bool) -> bool {
+    on || (default && !off)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::Format;
+
+    #[test]
+    fn test_parse_settings() {
+        let settings: Settings = Format::Yaml
+            .parse(
+                "detached: true
+name_collision: hyphen
+list_format:
+  project: \"P {}\"
+table_style: rounded",
+            )
+            .unwrap();
+
+        assert_eq!(
+            settings,
+            Settings {
+                detached: true,
+                name_collision: NameCollision::Hyphen,
+                list_format: ListFormat {
+                    project: Some("P {}".to_string()),
+                    ..Default::default()
+                },
+                table_style: TableStyle::Rounded,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            Format::Yaml.parse::<Settings>("{}").unwrap(),
+            Settings::default()
+        );
+    }
+
+    #[test]
+    fn test_resolve_flag() {
+        assert!(resolve_flag(false, false, true));
+        assert!(!resolve_flag(false, true, true));
+        assert!(resolve_flag(true, false, false));
+        assert!(!resolve_flag(false, false, false));
+    }
+}
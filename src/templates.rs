@@ -1,23 +1,280 @@
 use crate::{
-    helpers::{get_config_dir, Exit},
+    environment::{load_env, merge_env},
+    error::MoxideError,
+    formats::{config_files, read_config_file},
+    helpers::{absolute_path_from, apply_if_some, get_config_dir},
+    hooks::{chain_hooks, Hooks},
+    index::{find_by_file_stem, find_config_file, name_index, NamedFile},
+    matching::{not_found, resolve_name},
+    session::SessionBuilder,
+    tmux::{OriginKind, STOP_KEYS_OPTION},
+    variables::{
+        deserialize_optional_scalar, deserialize_values, resolve_variables, substitute_windows,
+        Scalar, Variable,
+    },
     widgets::table::Table,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
-use tmux_interface::{Tmux, TmuxCommand};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+use tmux_interface::{
+    RespawnPane, SelectPane, SetOption, SetWindowOption, SplitWindow, Tmux, TmuxCommand,
+};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug, Default)]
 pub struct Template {
     pub name: String,
     pub hidden: Option<bool>,
+    /// The name of a template whose windows and variables are inherited
+    pub extends: Option<String>,
+    /// Names of inherited windows that are dropped
+    pub remove_windows: Option<Vec<String>>,
+    pub variables: Option<BTreeMap<String, Variable>>,
+    /// Environment variables of the whole session
+    #[serde(default, deserialize_with = "deserialize_values")]
+    #[schemars(with = "Option<BTreeMap<String, Scalar>>")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// A `.env` file relative to the start directory
+    pub env_file: Option<PathBuf>,
+    pub hooks: Option<Hooks>,
+    /// Tmux options of the session, applied with `set-option`
+    #[serde(default, deserialize_with = "deserialize_values")]
+    #[schemars(with = "Option<BTreeMap<String, Scalar>>")]
+    pub options: Option<BTreeMap<String, String>>,
+    #[serde(default)]
     pub windows: Vec<Window>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+impl Template {
+    /// The session `name` of the template defined in `config_path` in `dir`, `vars` are the
+    /// values of its variables
+    pub fn session(
+        self,
+        config_path: Option<PathBuf>,
+        name: String,
+        dir: Option<PathBuf>,
+        vars: HashMap<String, String>,
+        prompt: impl FnMut(&str, &Variable) -> Result<String, MoxideError>,
+    ) -> Result<SessionBuilder, MoxideError> {
+        let values = resolve_variables(self.variables.as_ref(), vars, prompt)?;
+        let env = load_env(self.env, self.env_file.as_deref(), dir.as_deref())?;
+
+        let builder = SessionBuilder::new(name)
+            .origin(OriginKind::Template, &self.name, config_path)
+            .windows(substitute_windows(&self.windows, &values))
+            .env(env)
+            .hooks(self.hooks)
+            .options(self.options);
+        Ok(apply_if_some(builder, dir, SessionBuilder::root_dir))
+    }
+
+    /// Applies this template on top of its parent, windows with the name of an inherited window
+    /// replace it and all other windows are appended
+    fn merge_onto(self, parent: Self) -> Self {
+        let removed = self.remove_windows.unwrap_or_default();
+        let mut windows: Vec<_> = parent
+            .windows
+            .into_iter()
+            .filter(|window| {
+                window
+                    .name
+                    .as_ref()
+                    .is_none_or(|name| !removed.contains(name))
+            })
+            .collect();
+
+        for window in self.windows {
+            let existing = windows
+                .iter_mut()
+                .find(|existing| window.name.is_some() && existing.name == window.name);
+            match existing {
+                Some(existing) => *existing = window,
+                None => windows.push(window),
+            }
+        }
+
+        let variables = match (parent.variables, self.variables) {
+            (Some(mut parent_vars), Some(vars)) => {
+                parent_vars.extend(vars);
+                Some(parent_vars)
+            }
+            (parent_vars, vars) => vars.or(parent_vars),
+        };
+
+        Self {
+            name: self.name,
+            hidden: self.hidden,
+            extends: None,
+            remove_windows: None,
+            variables,
+            env: merge_env(parent.env, self.env),
+            env_file: self.env_file.or(parent.env_file),
+            hooks: chain_hooks(parent.hooks, self.hooks),
+            options: merge_env(parent.options, self.options),
+            windows,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq, Clone)]
 pub struct Window {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<String>,
-    pub panes: Vec<String>,
+    /// The working directory of all panes, relative to the root directory or absolute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// Environment variables of all panes
+    #[serde(
+        default,
+        deserialize_with = "deserialize_values",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<BTreeMap<String, Scalar>>")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// Tmux options of the window, applied with `set-window-option`
+    #[serde(
+        default,
+        deserialize_with = "deserialize_values",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<BTreeMap<String, Scalar>>")]
+    pub options: Option<BTreeMap<String, String>>,
+    /// Selects the window once the session is set up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<bool>,
+    pub panes: Vec<Pane>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum Pane {
+    Command(String),
+    Detailed(PaneConfig),
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PaneConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Commands that are run one after another, after `command`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    /// The working directory of the pane, relative to the root directory or absolute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_values",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<BTreeMap<String, Scalar>>")]
+    pub env: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<bool>,
+    /// Zooms the pane, a zoomed pane is focused as well
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom: Option<bool>,
+    /// How the pane is split off the previous pane, ignored for the first pane
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split: Option<Split>,
+    /// Keys that `moxide stop` sends to the pane before the session is killed, like `C-c`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Split {
+    pub direction: Option<SplitDirection>,
+    /// Either a number of lines/columns or a percentage like `30%`
+    #[serde(default, deserialize_with = "deserialize_optional_scalar")]
+    #[schemars(with = "Option<Scalar>")]
+    pub size: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitDirection {
+    /// Panes next to each other
+    Horizontal,
+    /// Panes above each other
+    Vertical,
+}
+
+impl Pane {
+    /// All commands of the pane in the order they are run
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        let (command, commands) = match self {
+            Self::Command(command) => (Some(command.as_str()), &[][..]),
+            Self::Detailed(config) => (config.command.as_deref(), &config.commands[..]),
+        };
+        command
+            .into_iter()
+            .chain(commands.iter().map(String::as_str))
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        match self {
+            Self::Command(_) => None,
+            Self::Detailed(config) => config.dir.as_deref(),
+        }
+    }
+
+    pub fn env(&self) -> Option<&BTreeMap<String, String>> {
+        match self {
+            Self::Command(_) => None,
+            Self::Detailed(config) => config.env.as_ref(),
+        }
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Self::Command(_) => None,
+            Self::Detailed(config) => config.title.as_deref(),
+        }
+    }
+
+    pub fn stop(&self) -> Option<&str> {
+        match self {
+            Self::Command(_) => None,
+            Self::Detailed(config) => config.stop.as_deref(),
+        }
+    }
+
+    pub fn focus(&self) -> bool {
+        match self {
+            Self::Command(_) => false,
+            Self::Detailed(config) => config.focus.unwrap_or(false),
+        }
+    }
+
+    pub fn zoom(&self) -> bool {
+        match self {
+            Self::Command(_) => false,
+            Self::Detailed(config) => config.zoom.unwrap_or(false),
+        }
+    }
+
+    pub fn split(&self) -> Option<&Split> {
+        match self {
+            Self::Command(_) => None,
+            Self::Detailed(config) => config.split.as_ref(),
+        }
+    }
+}
+
+impl From<&str> for Pane {
+    fn from(value: &str) -> Self {
+        Self::Command(value.to_string())
+    }
 }
 
 impl From<&Window> for Table<String, String> {
@@ -28,95 +285,307 @@ impl From<&Window> for Table<String, String> {
     }
 }
 
-pub fn find_template(name: &str) -> Option<Template> {
-    let templates_dir = get_config_dir().join("templates/");
-
-    let file_path = templates_dir.join(format!("{name}.yaml"));
+fn find_template_file(name: &str) -> Result<Option<Template>, MoxideError> {
+    let templates_dir = get_config_dir()?.join("templates/");
 
-    let is_valid_path = file_path.exists() && file_path.is_file();
-    let matching_path = is_valid_path.then_some(file_path);
+    find_config_file(&templates_dir, name)
+}
 
-    let matching_template = matching_path.and_then(|path| {
-        let content = fs::read_to_string(&path).ok()?;
-        let template = serde_yaml::from_str::<Template>(&content).ok()?;
-        (template.name == name).then_some(template)
-    });
+fn resolve_extends(template: Template, chain: &mut Vec<String>) -> Result<Template, MoxideError> {
+    let Some(parent_name) = template.extends.clone() else {
+        return Ok(template);
+    };
 
-    if matching_template.is_some() {
-        return matching_template;
+    let is_cycle = chain.contains(&parent_name);
+    chain.push(parent_name.clone());
+    if is_cycle {
+        return Err(MoxideError::Cycle(chain.clone()));
     }
 
-    fs::read_dir(&templates_dir)
-        .exit(1, "Can't read template config")
-        .find_map(|entry| {
-            let path = entry.ok()?.path();
-            if !path.is_file() {
-                return None;
-            }
+    let parent =
+        find_template_file(&parent_name)?.ok_or_else(|| not_found("Template", &parent_name))?;
+    let parent = resolve_extends(parent, chain)?;
 
-            let content = fs::read_to_string(&path).ok()?;
-            let template = serde_yaml::from_str::<Template>(&content).ok()?;
-            (template.name == name).then_some(template)
-        })
+    Ok(template.merge_onto(parent))
 }
 
-pub fn parse_template_config() -> impl Iterator<Item = Template> {
-    let templates_content =
-        fs::read_dir(get_config_dir().join("templates/")).exit(1, "Can't read template config");
+/// Finds a template by its name, or by a unique case-insensitive match or prefix of it, and
+/// resolves the templates it extends
+pub fn find_template(name: &str) -> Result<Template, MoxideError> {
+    read_template(&find_template_path(name)?)
+}
 
-    templates_content.filter_map(|entry| {
-        let entry = entry.ok()?;
-        let path = entry.path();
-        if !path.is_file() {
-            return None;
-        }
+/// Reads a template file and resolves the templates it extends
+pub fn read_template(path: &Path) -> Result<Template, MoxideError> {
+    let template: Template = read_config_file(path)?;
+    let name = template.name.clone();
+    resolve_extends(template, &mut vec![name])
+}
 
-        let content = fs::read_to_string(path).ok()?;
-        serde_yaml::from_str::<Template>(&content).ok()
-    })
+/// Finds the file of a template the same way as `find_template`
+pub fn find_template_path(name: &str) -> Result<PathBuf, MoxideError> {
+    resolve_name(name, template_files()?, |file| &file.name)
+        .map(|file| file.path)
+        .ok_or_else(|| not_found("Template", name))
+}
+
+/// Finds the file of a template like `find_template_path`, or by its file name if no template
+/// matches, so files with syntax errors can be found to fix them
+pub fn find_template_source(name: &str) -> Result<PathBuf, MoxideError> {
+    find_template_path(name)
+        .or_else(|err| find_by_file_stem(&get_config_dir()?.join("templates/"), name)?.ok_or(err))
+}
+
+/// The names of all templates, without parsing files that didn't change since they were indexed
+pub fn template_files() -> Result<Vec<NamedFile>, MoxideError> {
+    name_index(&get_config_dir()?.join("templates/"))
+}
+
+pub fn parse_template_config() -> Result<impl Iterator<Item = Template>, MoxideError> {
+    let files = config_files(&get_config_dir()?.join("templates/"))?;
+
+    Ok(files.into_iter().filter_map(|path| {
+        let template = read_config_file::<Template>(&path).ok()?;
+        let name = template.name.clone();
+        resolve_extends(template, &mut vec![name]).ok()
+    }))
 }
 
 pub fn apply_windows<'a>(
     tmux: Tmux<'a>,
     windows: &'a [Window],
     dir: Option<&'a PathBuf>,
-) -> Tmux<'a> {
-    let enumerated = windows.iter().enumerate();
-    enumerated.fold(tmux, |tmux, (window_idx, window)| {
-        let cmd = build_tmux_command(window_idx, window, dir);
-        let tmux = add_panes_to_tmux(tmux.add_command(cmd), &window.panes, dir);
+) -> Result<Tmux<'a>, MoxideError> {
+    let mut enumerated = windows.iter().enumerate();
+    let tmux = enumerated.try_fold(tmux, |tmux, (window_idx, window)| {
+        let window_dir = resolve_dir(dir, window.dir.as_deref())?.or_else(|| dir.cloned());
+        let pane_dirs = window
+            .panes
+            .iter()
+            .map(|pane| Ok(resolve_dir(dir, pane.dir())?.or_else(|| window_dir.clone())))
+            .collect::<Result<Vec<_>, MoxideError>>()?;
+        let first_dir = pane_dirs.first().cloned().unwrap_or(window_dir);
+
+        let pane_envs: Vec<_> = window
+            .panes
+            .iter()
+            .map(|pane| merge_env(window.env.clone(), pane.env().cloned()))
+            .collect();
+        let first_env = pane_envs.first().cloned().unwrap_or(window.env.clone());
 
-        match window.layout.as_ref() {
+        let mut cmd = build_tmux_command(window_idx, window, first_dir.as_ref());
+        if window_idx != 0 {
+            push_env(&mut cmd, first_env.as_ref());
+        }
+        let tmux = tmux.add_command(cmd);
+
+        // The first window is created together with the session in the root directory
+        let needs_respawn = first_dir.as_ref() != dir || first_env.is_some();
+        let tmux = match first_dir {
+            Some(first_dir) if window_idx == 0 && needs_respawn => {
+                let mut respawn = RespawnPane::new()
+                    .kill()
+                    .start_directory(first_dir.to_string_lossy().into_owned())
+                    .build();
+                push_env(&mut respawn, first_env.as_ref());
+                tmux.add_command(respawn)
+            }
+            _ => tmux,
+        };
+        let tmux = add_panes_to_tmux(tmux, &window.panes, &pane_dirs, &pane_envs);
+
+        let tmux = match window.layout.as_ref() {
             Some(layout) => {
                 let layout_cmd = TmuxCommand::select_layout().layout_name(layout);
                 tmux.add_command(layout_cmd)
             }
             None => tmux,
+        };
+        // Selecting a layout unzooms the window, so the pane is focused afterwards
+        let tmux = focus_pane(tmux, &window.panes);
+
+        // Applied after the panes were created, so `synchronize-panes` doesn't duplicate the
+        // commands that are sent to them
+        let options = window.options.iter().flatten();
+        Ok::<_, MoxideError>(options.fold(tmux, |tmux, (option, value)| {
+            let value = option_value(value);
+            tmux.add_command(SetWindowOption::new().option(option).value(value).build())
+        }))
+    })?;
+
+    // Every window is created after the previous one, so the last window is selected
+    let focused = windows
+        .iter()
+        .rposition(|window| window.focus.unwrap_or(false));
+    Ok(match focused.map(|focused| windows.len() - 1 - focused) {
+        Some(offset) if offset > 0 => {
+            tmux.add_command(TmuxCommand::select_window().target_window(format!(":-{offset}")))
         }
+        _ => tmux,
     })
 }
 
-fn add_panes_to_tmux<'a>(tmux: Tmux<'a>, panes: &[String], dir: Option<&'a PathBuf>) -> Tmux<'a> {
-    let enumerated = panes.iter().enumerate();
+/// Selects the focused pane and zooms it if requested
+fn focus_pane<'a>(tmux: Tmux<'a>, panes: &[Pane]) -> Tmux<'a> {
+    let Some(focused) = panes.iter().rposition(|pane| pane.focus() || pane.zoom()) else {
+        return tmux;
+    };
 
-    enumerated.fold(tmux, |tmux, (pane_idx, command)| {
-        let cmd: TmuxCommand = match (pane_idx, dir) {
-            (0, _) => TmuxCommand::new(),
-            (_, Some(d)) => TmuxCommand::split_window()
-                .start_directory(d.to_string_lossy())
-                .into(),
-            (_, None) => TmuxCommand::split_window().into(),
+    // Every pane is split off the previous one, so the last pane is active and the panes are
+    // numbered in the order they were created
+    let offset = panes.len() - 1 - focused;
+    let tmux = tmux.add_command(SelectPane::new().target_pane(format!(":.-{offset}")));
+
+    if panes[focused].zoom() {
+        tmux.add_command(TmuxCommand::resize_pane().zoom())
+    } else {
+        tmux
+    }
+}
+
+/// The layouts tmux knows by name, `select-layout` accepts unique prefixes of them as well
+const LAYOUT_NAMES: [&str; 7] = [
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-horizontal-mirrored",
+    "main-vertical",
+    "main-vertical-mirrored",
+    "tiled",
+];
+
+/// The checksum tmux puts in front of a custom layout
+fn layout_checksum(layout: &str) -> u16 {
+    layout.bytes().fold(0, |checksum: u16, byte| {
+        checksum.rotate_right(1).wrapping_add(byte.into())
+    })
+}
+
+/// Checks if tmux accepts the layout, either a layout name or a custom layout like the ones
+/// printed by `list-windows`
+pub fn is_valid_layout(layout: &str) -> bool {
+    let matching_names = LAYOUT_NAMES
+        .iter()
+        .filter(|name| name.starts_with(layout))
+        .count();
+    if LAYOUT_NAMES.contains(&layout) || (!layout.is_empty() && matching_names == 1) {
+        return true;
+    }
+
+    let Some((checksum, body)) = layout.split_once(',') else {
+        return false;
+    };
+    checksum.len() == 4
+        && u16::from_str_radix(checksum, 16).is_ok_and(|checksum| checksum == layout_checksum(body))
+}
+
+/// Converts yaml booleans into the values tmux expects for flag options
+fn option_value(value: &str) -> &str {
+    match value {
+        "true" => "on",
+        "false" => "off",
+        value => value,
+    }
+}
+
+/// Sets the tmux options of a session
+pub fn apply_options<'a>(
+    tmux: Tmux<'a>,
+    session_name: &str,
+    options: Option<&BTreeMap<String, String>>,
+) -> Tmux<'a> {
+    options
+        .into_iter()
+        .flatten()
+        .fold(tmux, |tmux, (option, value)| {
+            tmux.add_command(
+                SetOption::new()
+                    .target_pane(session_name.to_string())
+                    .option(option.clone())
+                    .value(option_value(value).to_string())
+                    .build(),
+            )
+        })
+}
+
+/// Resolves the directory of a window or pane relative to the root directory
+fn resolve_dir(root: Option<&PathBuf>, dir: Option<&Path>) -> Result<Option<PathBuf>, MoxideError> {
+    dir.map(|dir| absolute_path_from(root.map(PathBuf::as_path), dir).map_err(MoxideError::io(dir)))
+        .transpose()
+}
+
+/// Adds a `-e KEY=VALUE` option for every variable
+pub fn push_env<'a>(cmd: &mut TmuxCommand<'a>, env: Option<&BTreeMap<String, String>>) {
+    for (key, value) in env.into_iter().flatten() {
+        cmd.push_option("-e", format!("{key}={value}"));
+    }
+}
+
+fn split_command<'a>(
+    pane: &Pane,
+    dir: Option<&PathBuf>,
+    env: Option<&BTreeMap<String, String>>,
+) -> TmuxCommand<'a> {
+    let split = pane.split();
+    let split_window = match split.and_then(|split| split.direction) {
+        Some(SplitDirection::Horizontal) => SplitWindow::new().horizontal(),
+        Some(SplitDirection::Vertical) => SplitWindow::new().vertical(),
+        None => SplitWindow::new(),
+    };
+    let split_window = match dir {
+        Some(d) => split_window.start_directory(d.to_string_lossy().into_owned()),
+        None => split_window,
+    };
+
+    let mut cmd = split_window.build();
+    if let Some(size) = split.and_then(|split| split.size.as_ref()) {
+        cmd.push_option("-l", size.clone());
+    }
+    push_env(&mut cmd, env);
+
+    cmd
+}
+
+fn add_panes_to_tmux<'a>(
+    tmux: Tmux<'a>,
+    panes: &[Pane],
+    dirs: &[Option<PathBuf>],
+    envs: &[Option<BTreeMap<String, String>>],
+) -> Tmux<'a> {
+    let enumerated = panes.iter().zip(dirs).zip(envs).enumerate();
+
+    enumerated.fold(tmux, |tmux, (pane_idx, ((pane, dir), env))| {
+        let tmux = if pane_idx == 0 {
+            tmux
+        } else {
+            tmux.add_command(split_command(pane, dir.as_ref(), env.as_ref()))
+        };
+        let tmux = match pane.title() {
+            Some(title) => tmux.add_command(SelectPane::new().title(title.to_string())),
+            None => tmux,
+        };
+        // Stored in the pane, so `moxide stop` doesn't need the config that created the session
+        let tmux = match pane.stop() {
+            Some(keys) => tmux.add_command(
+                SetOption::new()
+                    .pane()
+                    .option(STOP_KEYS_OPTION)
+                    .value(keys.to_string())
+                    .build(),
+            ),
+            None => tmux,
         };
 
-        tmux.add_command(cmd)
-            .add_command(TmuxCommand::send_keys().key(format!("{command}\r")))
+        pane.commands().fold(tmux, |tmux, command| {
+            tmux.add_command(TmuxCommand::send_keys().key(format!("{command}\r")))
+        })
     })
 }
 
 fn build_tmux_command<'a>(
     window_idx: usize,
     window: &'a Window,
-    dir: Option<&'a PathBuf>,
+    dir: Option<&PathBuf>,
 ) -> TmuxCommand<'a> {
     if window_idx == 0 {
         window
@@ -130,8 +599,129 @@ fn build_tmux_command<'a>(
             TmuxCommand::new_window().window_name(name)
         });
         match dir {
-            Some(d) => new_win.start_directory(d.to_string_lossy()).into(),
+            Some(d) => new_win
+                .start_directory(d.to_string_lossy().into_owned())
+                .into(),
             None => new_win.into(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_onto() {
+        let base = serde_yaml::from_str::<Template>(
+            "name: Base
+windows:
+  - name: Editor
+    panes:
+      - nvim
+  - name: Shell
+    panes:
+      - ''
+  - name: Git
+    panes:
+      - lazygit",
+        )
+        .unwrap();
+        let rust = serde_yaml::from_str::<Template>(
+            "name: Rust
+extends: Base
+remove_windows:
+  - Git
+windows:
+  - name: Shell
+    panes:
+      - cargo check
+  - name: Cargo
+    panes:
+      - cargo build",
+        )
+        .unwrap();
+
+        let merged = rust.merge_onto(base);
+        let names: Vec<_> = merged
+            .windows
+            .iter()
+            .filter_map(|window| window.name.as_deref())
+            .collect();
+
+        assert_eq!(merged.name, "Rust");
+        assert_eq!(names, vec!["Editor", "Shell", "Cargo"]);
+        assert_eq!(merged.windows[1].panes, vec![Pane::from("cargo check")]);
+    }
+
+    #[test]
+    fn test_structured_panes() {
+        let window = serde_yaml::from_str::<Window>(
+            "panes:
+  - nvim
+  - commands:
+      - cargo build
+      - cargo test
+    title: Cargo
+    env:
+      RUST_LOG: debug
+    split:
+      direction: horizontal
+      size: 30%",
+        )
+        .unwrap();
+
+        assert_eq!(window.panes[0], Pane::from("nvim"));
+        let pane = &window.panes[1];
+        assert_eq!(
+            pane.commands().collect::<Vec<_>>(),
+            vec!["cargo build", "cargo test"]
+        );
+        assert_eq!(pane.title(), Some("Cargo"));
+        assert_eq!(
+            pane.split(),
+            Some(&Split {
+                direction: Some(SplitDirection::Horizontal),
+                size: Some("30%".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_window_options() {
+        let window = serde_yaml::from_str::<Window>(
+            "options:
+  synchronize-panes: true
+  remain-on-exit: on
+  pane-border-lines: heavy
+panes:
+  - ''",
+        )
+        .unwrap();
+
+        let options: Vec<_> = window
+            .options
+            .iter()
+            .flatten()
+            .map(|(option, value)| (option.as_str(), option_value(value)))
+            .collect();
+        assert_eq!(
+            options,
+            vec![
+                ("pane-border-lines", "heavy"),
+                ("remain-on-exit", "on"),
+                ("synchronize-panes", "on"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_valid_layout() {
+        assert!(is_valid_layout("tiled"));
+        assert!(is_valid_layout("even-h"));
+        assert!(is_valid_layout("419a,80x24,0,0[80x12,0,0,1,80x11,0,13,2]"));
+        assert!(!is_valid_layout("main"));
+        assert!(!is_valid_layout("tilted"));
+        assert!(!is_valid_layout("419b,80x24,0,0[80x12,0,0,1,80x11,0,13,2]"));
+    }
+}
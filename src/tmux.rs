@@ -1,6 +1,74 @@
-use crate::helpers::runs_in_tmux;
-use std::borrow::Cow;
-use tmux_interface::{Error, HasSession, Tmux, TmuxCommand};
+use crate::{error::MoxideError, helpers::runs_in_tmux};
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fmt,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+use tmux_interface::{
+    DisplayMessage, KillSession, ListClients, ListPanes, ListSessions, RenameSession, SendKeys,
+    SetOption, ShowHooks, SwitchClient, Tmux, TmuxCommand, TmuxOutput,
+};
+
+/// The pane option that holds the keys `moxide stop` sends to the pane
+pub const STOP_KEYS_OPTION: &str = "@moxide-stop";
+/// The session options that store the origin of a session
+const KIND_OPTION: &str = "@moxide-kind";
+const NAME_OPTION: &str = "@moxide-name";
+const ROOT_OPTION: &str = "@moxide-root";
+const CONFIG_PATH_OPTION: &str = "@moxide-config-path";
+
+/// How a new session is named when a session with its name already exists
+#[derive(Deserialize, JsonSchema, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NameCollision {
+    /// Appends a counter in parentheses like `name(1)`
+    #[default]
+    Counter,
+    /// Appends a counter after a hyphen like `name-1`
+    Hyphen,
+    /// Fails instead of renaming the session
+    Fail,
+}
+
+impl NameCollision {
+    fn nth_name(self, name: &str, counter: usize) -> String {
+        match self {
+            Self::Counter | Self::Fail => format!("{name}({counter})"),
+            Self::Hyphen => format!("{name}-{counter}"),
+        }
+    }
+}
+
+/// Runs the commands and turns a failing tmux into an error
+pub fn run(tmux: Tmux) -> Result<TmuxOutput, MoxideError> {
+    let output = tmux
+        .output()
+        .map_err(|err| MoxideError::Tmux(format!("Can't run tmux: {err}")))?;
+    if output.success() {
+        return Ok(output);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.0.stderr).trim().to_string();
+    match stderr.strip_prefix("duplicate session: ") {
+        Some(name) => Err(MoxideError::SessionExists(name.to_string())),
+        None => Err(MoxideError::Tmux(format!("Tmux failed: {stderr}"))),
+    }
+}
+
+/// Turns a name into a valid session name, tmux would replace `.` and `:` itself and the session
+/// couldn't be found by its original name. `replacements` are applied first
+pub fn normalize_name(name: &str, replacements: &BTreeMap<String, String>) -> String {
+    replacements
+        .iter()
+        .fold(name.to_string(), |name, (from, to)| name.replace(from, to))
+        .replace(['.', ':'], "_")
+}
 
 pub fn attach<'a, S: Into<Cow<'a, str>>>(name: S) -> TmuxCommand<'a> {
     if runs_in_tmux() {
@@ -10,21 +78,486 @@ pub fn attach<'a, S: Into<Cow<'a, str>>>(name: S) -> TmuxCommand<'a> {
     }
 }
 
-pub fn session_exists<'a, S: Into<Cow<'a, str>>>(name: S) -> Result<bool, Error> {
-    let has_session = HasSession::new().target_session(name);
-    Tmux::with_command(has_session)
+/// The stdout of tmux, or `None` if it failed because there's no such session or server
+fn output_lines(tmux: Tmux) -> Result<Option<Vec<String>>, MoxideError> {
+    let output = tmux
         .output()
-        .map(|x| x.success())
+        .map_err(|err| MoxideError::Tmux(format!("Can't run tmux: {err}")))?;
+    if !output.success() {
+        return Ok(None);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.0.stdout);
+    Ok(Some(stdout.lines().map(str::to_string).collect()))
+}
+
+/// The commands of a global array hook like `session-closed` by their index
+pub(crate) fn global_hooks(hook: &str) -> HashMap<usize, String> {
+    let prefix = format!("{hook}[");
+    let lines = output_lines(Tmux::with_command(ShowHooks::new().global().build()));
+
+    lines
+        .ok()
+        .flatten()
+        .into_iter()
+        .flatten()
+        .filter_map(|line| {
+            let (index, command) = line.strip_prefix(&prefix)?.split_once(']')?;
+            Some((index.parse().ok()?, command.trim_start().to_string()))
+        })
+        .collect()
+}
+
+/// What a session was started from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OriginKind {
+    Project,
+    Template,
+    Directory,
+}
+
+impl OriginKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Template => "template",
+            Self::Directory => "directory",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "project" => Some(Self::Project),
+            "template" => Some(Self::Template),
+            "directory" => Some(Self::Directory),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OriginKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where a session that moxide started came from, it's stored in user options of the session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub kind: OriginKind,
+    /// The name of the project, template or directory
+    pub name: String,
+    pub root: Option<PathBuf>,
+    /// The file that defines the project, template or directory
+    pub config_path: Option<PathBuf>,
+}
+
+impl Origin {
+    /// Stores the origin in the user options of the session
+    pub fn apply<'a>(&self, tmux: Tmux<'a>, session_name: &str) -> Tmux<'a> {
+        let path_value = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+        };
+        let options = [
+            (KIND_OPTION, Some(self.kind.as_str().to_string())),
+            (NAME_OPTION, Some(self.name.clone())),
+            (ROOT_OPTION, path_value(&self.root)),
+            (CONFIG_PATH_OPTION, path_value(&self.config_path)),
+        ];
+
+        options
+            .into_iter()
+            .filter_map(|(option, value)| Some((option, value?)))
+            .fold(tmux, |tmux, (option, value)| {
+                tmux.add_command(
+                    SetOption::new()
+                        .target_pane(session_name.to_string())
+                        .option(option)
+                        .value(value)
+                        .build(),
+                )
+            })
+    }
+}
+
+/// A running session and the origin moxide stored in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionStatus {
+    pub name: String,
+    pub windows: usize,
+    /// The number of attached clients
+    pub attached: usize,
+    pub created: String,
+    /// `None` if the session wasn't started by moxide
+    pub origin: Option<Origin>,
+}
+
+impl SessionStatus {
+    fn format() -> String {
+        format!(
+            "#{{session_name}}\t#{{session_windows}}\t#{{session_attached}}\t#{{t:session_created}}\t#{{{KIND_OPTION}}}\t#{{{NAME_OPTION}}}\t#{{{ROOT_OPTION}}}\t#{{{CONFIG_PATH_OPTION}}}"
+        )
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.splitn(8, '\t');
+        let name = parts.next()?.to_string();
+        let windows = parts.next()?.parse().ok()?;
+        let attached = parts.next()?.parse().ok()?;
+        let created = parts.next()?.to_string();
+
+        let kind = OriginKind::parse(parts.next()?);
+        let origin_name = parts.next()?.to_string();
+        let path = |value: &str| (!value.is_empty()).then(|| PathBuf::from(value));
+        let root = parts.next().and_then(path);
+        let config_path = parts.next().and_then(path);
+
+        Some(Self {
+            name,
+            windows,
+            attached,
+            created,
+            origin: kind.map(|kind| Origin {
+                kind,
+                name: origin_name,
+                root,
+                config_path,
+            }),
+        })
+    }
+
+    /// Lists the running sessions, without a tmux server there are none
+    pub fn fetch() -> Result<Vec<Self>, MoxideError> {
+        // Tmux replaces the tabs unless it's sure that the output is UTF-8
+        let list_sessions = ListSessions::new().format(Self::format());
+        let lines = output_lines(Tmux::with_command(list_sessions).force_utf8())?;
+
+        Ok(lines
+            .into_iter()
+            .flatten()
+            .filter_map(|line| Self::parse(&line))
+            .collect())
+    }
+}
+
+/// The running sessions, fetched once with `list-sessions` and shared by every check
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Sessions {
+    names: HashSet<String>,
+    /// The origins of the sessions moxide started, by the name of the session
+    origins: HashMap<String, Origin>,
+}
+
+impl Sessions {
+    /// Lists the running sessions, without a tmux server there are none
+    pub fn fetch() -> Result<Self, MoxideError> {
+        let mut sessions = Self::default();
+        for status in SessionStatus::fetch()? {
+            if let Some(origin) = status.origin {
+                sessions.origins.insert(status.name.clone(), origin);
+            }
+            sessions.names.insert(status.name);
+        }
+
+        Ok(sessions)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// The sessions that were started by moxide
+    pub fn managed(&self) -> impl Iterator<Item = &str> {
+        self.origins.keys().map(String::as_str)
+    }
+
+    /// Whether a session with exactly this name exists
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// Whether a session was started from the project, template or directory, even if it was
+    /// renamed because of a name collision
+    pub fn runs(&self, kind: OriginKind, name: &str) -> bool {
+        self.origins
+            .values()
+            .any(|origin| origin.kind == kind && origin.name == name)
+    }
+
+    /// The origin of a session, `None` if it wasn't started by moxide
+    pub fn origin(&self, session_name: &str) -> Option<&Origin> {
+        self.origins.get(session_name)
+    }
+
+    /// The sessions that were started from the project, template or directory and the session
+    /// named `session_name`, which may have been started without moxide
+    pub fn sessions_of(&self, kind: OriginKind, name: &str, session_name: &str) -> Vec<&str> {
+        let mut sessions: Vec<&str> = self
+            .origins
+            .iter()
+            .filter(|(_, origin)| origin.kind == kind && origin.name == name)
+            .map(|(session, _)| session.as_str())
+            .sorted()
+            .collect();
+        if let Some(session) = self.names.get(session_name) {
+            if !sessions.contains(&session.as_str()) {
+                sessions.push(session);
+            }
+        }
+        sessions
+    }
+
+    /// The name itself if no session uses it yet, otherwise a name chosen by `collision`
+    pub fn unused_name(&self, name: &str, collision: NameCollision) -> Result<String, MoxideError> {
+        let mut counter = 0;
+        let mut new_name = name.to_string();
+
+        while self.contains(&new_name) {
+            if collision == NameCollision::Fail {
+                return Err(MoxideError::SessionExists(new_name));
+            }
+            counter += 1;
+            new_name = collision.nth_name(name, counter);
+        }
+
+        Ok(new_name)
+    }
 }
 
-pub fn get_unused_name(name: &str) -> String {
-    let mut counter = 0;
-    let mut new_name = name.to_string();
+impl FromIterator<String> for Sessions {
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        Self {
+            names: iter.into_iter().collect(),
+            origins: HashMap::new(),
+        }
+    }
+}
+
+/// A pane of a session that is stopped
+struct StoppingPane {
+    id: String,
+    /// Whether the shell of the pane is in the foreground again
+    is_idle: bool,
+    stop_keys: String,
+}
+
+/// The panes of the session that have stop keys
+fn stopping_panes(session_name: &str) -> Result<Vec<StoppingPane>, MoxideError> {
+    // The keys are last, so they may contain the separator
+    let format = format!(
+        "#{{pane_id}}|#{{pane_dead}}|#{{pane_current_command}}|#{{default-shell}}|#{{{STOP_KEYS_OPTION}}}"
+    );
+    let list_panes = ListPanes::new()
+        .session()
+        .target(format!("={session_name}"))
+        .format(format);
+    let lines = output_lines(Tmux::with_command(list_panes))?;
+
+    Ok(lines
+        .into_iter()
+        .flatten()
+        .filter_map(|line| {
+            let mut parts = line.splitn(5, '|');
+            let id = parts.next()?.to_string();
+            let is_dead = parts.next()? == "1";
+            let command = parts.next()?;
+            let shell = Path::new(parts.next()?).file_name()?.to_string_lossy();
+            let stop_keys = parts.next().filter(|keys| !keys.is_empty())?;
+
+            Some(StoppingPane {
+                id,
+                is_idle: is_dead || command == shell,
+                stop_keys: stop_keys.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Sends the stop keys to the panes of the session and waits until their shells are in the
+/// foreground again or `timeout` passed
+pub fn stop_panes(session_name: &str, timeout: Duration) -> Result<(), MoxideError> {
+    let panes = stopping_panes(session_name)?;
+    let send_keys = panes.iter().map(|pane| {
+        pane.stop_keys
+            .split_whitespace()
+            .fold(SendKeys::new().target_pane(pane.id.clone()), |cmd, key| {
+                cmd.key(key.to_string())
+            })
+            .build()
+    });
+    let tmux = send_keys.fold(Tmux::new(), Tmux::add_command);
+    if !panes.is_empty() {
+        run(tmux)?;
+    }
 
-    while session_exists(&new_name).unwrap_or(false) {
-        counter += 1;
-        new_name = format!("{name}({counter})");
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if stopping_panes(session_name)?
+            .iter()
+            .all(|pane| pane.is_idle)
+        {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
     }
 
-    new_name
+    Ok(())
+}
+
+/// Stops the panes of the session and kills it afterwards
+pub fn stop_session(session_name: &str, timeout: Duration) -> Result<(), MoxideError> {
+    stop_panes(session_name, timeout)?;
+
+    let kill_session = KillSession::new().target_session(format!("={session_name}"));
+    run(Tmux::with_command(kill_session)).map(|_| ())
+}
+
+/// The session moxide runs in, `None` outside of tmux
+pub fn current_session() -> Result<Option<String>, MoxideError> {
+    if !runs_in_tmux() {
+        return Ok(None);
+    }
+
+    // Without a client tmux only knows the session through the pane
+    let display_message = DisplayMessage::new().print().message("#{session_name}");
+    let display_message = match env::var("TMUX_PANE") {
+        Ok(pane) => display_message.target_pane(pane),
+        Err(_) => display_message,
+    };
+    let lines = output_lines(Tmux::with_command(display_message))?;
+    Ok(lines.into_iter().flatten().next())
+}
+
+/// The directory a session was started in
+pub fn session_path(session_name: &str) -> Result<Option<PathBuf>, MoxideError> {
+    let display_message = DisplayMessage::new()
+        .print()
+        .target_pane(format!("={session_name}:"))
+        .message("#{session_path}");
+    let lines = output_lines(Tmux::with_command(display_message))?;
+
+    Ok(lines
+        .into_iter()
+        .flatten()
+        .next()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from))
+}
+
+pub fn rename_session(session_name: &str, new_name: &str) -> Result<(), MoxideError> {
+    let rename_session = RenameSession::new()
+        .target_session(format!("={session_name}"))
+        .new_name(new_name.to_string());
+    run(Tmux::with_command(rename_session)).map(|_| ())
+}
+
+/// Renames a running session, so a new session can be started with its name. Returns the new name
+/// of the old session
+pub fn move_aside(session_name: &str, sessions: &Sessions) -> Result<String, MoxideError> {
+    let old_name = sessions.unused_name(&format!("{session_name}-old"), NameCollision::Counter)?;
+    rename_session(session_name, &old_name)?;
+
+    Ok(old_name)
+}
+
+/// Moves the clients of the old session to the new session and kills the old session. It's done
+/// in a single call, because moxide itself may run in the old session
+pub fn replace_session(old_name: &str, new_name: &str, attach: bool) -> Result<(), MoxideError> {
+    let list_clients = ListClients::new()
+        .target_session(format!("={old_name}"))
+        .format("#{client_name}");
+    let clients = output_lines(Tmux::with_command(list_clients))?;
+
+    let tmux = clients
+        .into_iter()
+        .flatten()
+        .fold(Tmux::new(), |tmux, client| {
+            tmux.add_command(
+                SwitchClient::new()
+                    .target_client(client)
+                    .target_session(format!("={new_name}"))
+                    .build(),
+            )
+        })
+        .add_command(KillSession::new().target_session(format!("={old_name}")));
+    let tmux = if attach {
+        tmux.add_command(self::attach(new_name.to_string()))
+    } else {
+        tmux
+    };
+
+    run(tmux).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        let no_replacements = BTreeMap::new();
+        assert_eq!(normalize_name("foo.rs", &no_replacements), "foo_rs");
+        assert_eq!(normalize_name("a:b.c", &no_replacements), "a_b_c");
+        assert_eq!(normalize_name("moxide", &no_replacements), "moxide");
+
+        let replacements = BTreeMap::from([
+            (".".to_string(), "-".to_string()),
+            (" ".to_string(), String::new()),
+        ]);
+        assert_eq!(normalize_name("my foo.rs:1", &replacements), "myfoo-rs_1");
+    }
+
+    #[test]
+    fn test_unused_name() {
+        let sessions: Sessions = ["moxide", "moxide(1)", "moxide-web"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        assert_eq!(
+            sessions.unused_name("rust", NameCollision::Fail).unwrap(),
+            "rust"
+        );
+        assert_eq!(
+            sessions
+                .unused_name("moxide", NameCollision::Counter)
+                .unwrap(),
+            "moxide(2)"
+        );
+        assert_eq!(
+            sessions
+                .unused_name("moxide", NameCollision::Hyphen)
+                .unwrap(),
+            "moxide-1"
+        );
+        assert!(sessions.unused_name("moxide", NameCollision::Fail).is_err());
+    }
+
+    #[test]
+    fn test_parse_session_status() {
+        let status = SessionStatus::parse(
+            "web(1)\t2\t1\tThu Oct 15 10:00:00 2026\tproject\tweb\t/code/web\t",
+        )
+        .unwrap();
+        assert_eq!(
+            status,
+            SessionStatus {
+                name: "web(1)".to_string(),
+                windows: 2,
+                attached: 1,
+                created: "Thu Oct 15 10:00:00 2026".to_string(),
+                origin: Some(Origin {
+                    kind: OriginKind::Project,
+                    name: "web".to_string(),
+                    root: Some(PathBuf::from("/code/web")),
+                    config_path: None,
+                }),
+            }
+        );
+
+        let status =
+            SessionStatus::parse("scratch\t1\t0\tThu Oct 15 10:00:00 2026\t\t\t\t").unwrap();
+        assert_eq!(status.origin, None);
+    }
 }
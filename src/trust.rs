@@ -0,0 +1,120 @@
+use crate::{error::MoxideError, helpers::get_config_dir};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+/// Repository-local project files that are allowed to run commands, mapped to the hash of the
+/// content that was trusted
+type Allowlist = BTreeMap<PathBuf, String>;
+
+fn allowlist_path() -> Result<PathBuf, MoxideError> {
+    Ok(get_config_dir()?.join("trusted.yaml"))
+}
+
+fn read_allowlist(path: &Path) -> Result<Allowlist, MoxideError> {
+    if !path.exists() {
+        return Ok(Allowlist::new());
+    }
+
+    let content = fs::read_to_string(path).map_err(MoxideError::io(path))?;
+    serde_yaml::from_str(&content).map_err(|source| MoxideError::Parse {
+        path: path.to_path_buf(),
+        source: source.into(),
+    })
+}
+
+/// SHA-256, so a changed file can't be crafted to keep the hash of the trusted one
+fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn file_hash(file: &Path) -> Result<String, MoxideError> {
+    let content = fs::read(file).map_err(MoxideError::io(file))?;
+    Ok(content_hash(&content))
+}
+
+/// Whether the file is on the allowlist and wasn't changed since it was trusted
+pub fn is_trusted(file: &Path) -> Result<bool, MoxideError> {
+    is_allowed(&read_allowlist(&allowlist_path()?)?, file)
+}
+
+fn is_allowed(allowlist: &Allowlist, file: &Path) -> Result<bool, MoxideError> {
+    let Some(hash) = allowlist.get(file) else {
+        return Ok(false);
+    };
+
+    Ok(*hash == file_hash(file)?)
+}
+
+/// Adds the current content of the file to the allowlist
+pub fn trust(file: &Path) -> Result<(), MoxideError> {
+    let path = allowlist_path()?;
+    let mut allowlist = read_allowlist(&path)?;
+    allowlist.insert(file.to_path_buf(), file_hash(file)?);
+
+    let content = serde_yaml::to_string(&allowlist).map_err(|source| MoxideError::Parse {
+        path: path.clone(),
+        source: source.into(),
+    })?;
+    fs::write(&path, content).map_err(MoxideError::io(path))
+}
+
+/// Makes sure the file may run commands, asks for permission if it isn't trusted yet
+pub fn ensure_trusted(file: &Path) -> Result<(), MoxideError> {
+    if is_trusted(file)? {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        return Err(MoxideError::Untrusted(file.to_path_buf()));
+    }
+
+    eprint!(
+        "{} can run commands and wasn't trusted yet or was changed since. Trust it? [y/N] ",
+        file.display()
+    );
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(MoxideError::Input)?;
+    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+        return Err(MoxideError::Aborted);
+    }
+
+    trust(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_ne!(content_hash(b"name: a"), content_hash(b"name: b"));
+    }
+
+    #[test]
+    fn test_changed_file_is_untrusted() {
+        let file = std::env::temp_dir().join(format!("moxide-trust-{}.yaml", std::process::id()));
+        fs::write(&file, "name: Shop\nwindows: []\n").unwrap();
+        let allowlist = Allowlist::from([(file.clone(), file_hash(&file).unwrap())]);
+        assert!(is_allowed(&allowlist, &file).unwrap());
+
+        fs::write(&file, "name: Shop\nwindows: []\r").unwrap();
+        let trusted = is_allowed(&allowlist, &file).unwrap();
+        fs::remove_file(&file).unwrap();
+        assert!(!trusted);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{
+    error::MoxideError,
+    templates::{Pane, PaneConfig, Window},
+};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+#[derive(Deserialize, JsonSchema, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum Variable {
+    Default(
+        #[serde(deserialize_with = "deserialize_scalar")]
+        #[schemars(with = "Scalar")]
+        String,
+    ),
+    Detailed {
+        #[serde(default, deserialize_with = "deserialize_optional_scalar")]
+        #[schemars(with = "Option<Scalar>")]
+        default: Option<String>,
+        prompt: Option<String>,
+    },
+    /// `name:` without a value, the value has to be provided or entered
+    Required,
+}
+
+impl Variable {
+    pub fn default_value(&self) -> Option<&str> {
+        match self {
+            Self::Default(default) => Some(default),
+            Self::Detailed { default, .. } => default.as_deref(),
+            Self::Required => None,
+        }
+    }
+
+    pub fn prompt_text(&self) -> Option<&str> {
+        match self {
+            Self::Default(_) | Self::Required => None,
+            Self::Detailed { prompt, .. } => prompt.as_deref(),
+        }
+    }
+}
+
+/// A yaml scalar like `3000` or `true` that is used as a string
+pub(crate) struct Scalar(String);
+
+impl JsonSchema for Scalar {
+    fn schema_name() -> Cow<'static, str> {
+        "Scalar".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": ["string", "number", "boolean"] })
+    }
+}
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ScalarVisitor;
+
+        impl Visitor<'_> for ScalarVisitor {
+            type Value = Scalar;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string, number or boolean")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+                Ok(Scalar(value.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(ScalarVisitor)
+    }
+}
+
+pub fn deserialize_scalar<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Scalar::deserialize(deserializer).map(|scalar| scalar.0)
+}
+
+pub fn deserialize_optional_scalar<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Option::<Scalar>::deserialize(deserializer).map(|scalar| scalar.map(|scalar| scalar.0))
+}
+
+/// Deserializes a mapping of variable values, numbers and booleans are converted into strings
+pub fn deserialize_values<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromIterator<(String, String)>,
+{
+    let values = Option::<HashMap<String, Scalar>>::deserialize(deserializer)?;
+    Ok(values.map(|values| {
+        values
+            .into_iter()
+            .map(|(name, scalar)| (name, scalar.0))
+            .collect()
+    }))
+}
+
+/// A prompt that never asks, missing values are reported as `MissingVariable`
+pub fn no_prompt(name: &str, _: &Variable) -> Result<String, MoxideError> {
+    Err(MoxideError::MissingVariable(name.to_string()))
+}
+
+/// Merges the provided values with the defaults of the declared variables, missing values of
+/// required variables are asked for with `prompt`
+pub fn resolve_variables(
+    declared: Option<&BTreeMap<String, Variable>>,
+    mut provided: HashMap<String, String>,
+    mut prompt: impl FnMut(&str, &Variable) -> Result<String, MoxideError>,
+) -> Result<HashMap<String, String>, MoxideError> {
+    for (name, variable) in declared.into_iter().flatten() {
+        if provided.contains_key(name) {
+            continue;
+        }
+
+        let value = match variable.default_value() {
+            Some(default) => default.to_string(),
+            None => prompt(name, variable)?,
+        };
+        provided.insert(name.clone(), value);
+    }
+
+    Ok(provided)
+}
+
+/// Replaces every `{{ name }}` with the value of `name`, unknown variables are kept as they are
+pub fn substitute(text: &str, values: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let (before, after) = rest.split_at(start);
+        result.push_str(before);
+
+        let Some(end) = after.find("}}") else {
+            rest = after;
+            break;
+        };
+
+        let placeholder = &after[..end + 2];
+        match values.get(after[2..end].trim()) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(placeholder),
+        }
+        rest = &after[end + 2..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn substitute_path(path: &Path, values: &HashMap<String, String>) -> PathBuf {
+    PathBuf::from(substitute(&path.to_string_lossy(), values))
+}
+
+fn substitute_pane(pane: &Pane, values: &HashMap<String, String>) -> Pane {
+    match pane {
+        Pane::Command(command) => Pane::Command(substitute(command, values)),
+        Pane::Detailed(config) => Pane::Detailed(PaneConfig {
+            command: config
+                .command
+                .as_deref()
+                .map(|command| substitute(command, values)),
+            commands: config
+                .commands
+                .iter()
+                .map(|command| substitute(command, values))
+                .collect(),
+            dir: config
+                .dir
+                .as_deref()
+                .map(|dir| substitute_path(dir, values)),
+            env: config.env.as_ref().map(|env| {
+                env.iter()
+                    .map(|(key, value)| (key.clone(), substitute(value, values)))
+                    .collect()
+            }),
+            title: config
+                .title
+                .as_deref()
+                .map(|title| substitute(title, values)),
+            ..config.clone()
+        }),
+    }
+}
+
+pub fn substitute_windows(windows: &[Window], values: &HashMap<String, String>) -> Vec<Window> {
+    windows
+        .iter()
+        .map(|window| Window {
+            name: window.name.as_deref().map(|name| substitute(name, values)),
+            layout: window
+                .layout
+                .as_deref()
+                .map(|layout| substitute(layout, values)),
+            dir: window
+                .dir
+                .as_deref()
+                .map(|dir| substitute_path(dir, values)),
+            env: window.env.as_ref().map(|env| {
+                env.iter()
+                    .map(|(key, value)| (key.clone(), substitute(value, values)))
+                    .collect()
+            }),
+            options: window.options.as_ref().map(|options| {
+                options
+                    .iter()
+                    .map(|(option, value)| (option.clone(), substitute(value, values)))
+                    .collect()
+            }),
+            focus: window.focus,
+            panes: window
+                .panes
+                .iter()
+                .map(|pane| substitute_pane(pane, values))
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        let values = HashMap::from([
+            ("port".to_string(), "3000".to_string()),
+            ("bin".to_string(), "server".to_string()),
+        ]);
+
+        assert_eq!(
+            substitute("cargo run --bin {{bin}} -- -p {{ port }}", &values),
+            "cargo run --bin server -- -p 3000"
+        );
+        assert_eq!(
+            substitute("{{ unknown }} {{port", &values),
+            "{{ unknown }} {{port"
+        );
+    }
+
+    #[test]
+    fn test_resolve_variables() {
+        let declared = serde_yaml::from_str::<BTreeMap<String, Variable>>(
+            "port: 3000
+branch:
+  default: main
+  prompt: Which branch?",
+        )
+        .unwrap();
+        let provided = HashMap::from([("port".to_string(), "8080".to_string())]);
+
+        let values = resolve_variables(Some(&declared), provided, no_prompt).unwrap();
+        assert_eq!(values.get("port").map(String::as_str), Some("8080"));
+        assert_eq!(values.get("branch").map(String::as_str), Some("main"));
+    }
+
+    #[test]
+    fn test_required_variable() {
+        let declared =
+            serde_yaml::from_str::<BTreeMap<String, Variable>>("port: 3000\nbranch:").unwrap();
+        assert_eq!(declared.get("branch"), Some(&Variable::Required));
+        assert_eq!(declared["branch"].default_value(), None);
+        assert_eq!(declared["branch"].prompt_text(), None);
+
+        let provided = HashMap::from([("branch".to_string(), "dev".to_string())]);
+        let values = resolve_variables(Some(&declared), provided, no_prompt).unwrap();
+        assert_eq!(values.get("branch").map(String::as_str), Some("dev"));
+
+        let err = resolve_variables(Some(&declared), HashMap::new(), no_prompt).unwrap_err();
+        assert!(matches!(err, MoxideError::MissingVariable(name) if name == "branch"));
+    }
+}
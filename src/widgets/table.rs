@@ -1,14 +1,70 @@
 use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
 use std::fmt;
 
+/// The characters the border of a table is drawn with
+#[derive(Deserialize, JsonSchema, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    /// Box drawing characters with sharp corners
+    #[default]
+    Sharp,
+    /// Box drawing characters with rounded corners
+    Rounded,
+    /// Only ASCII characters
+    Ascii,
+}
+
+struct Border {
+    corners: [char; 4],
+    horizontal: char,
+    vertical: char,
+    joins: [char; 2],
+}
+
+impl TableStyle {
+    const fn border(self) -> Border {
+        match self {
+            Self::Sharp => Border {
+                corners: ['┌', '┐', '└', '┘'],
+                horizontal: '─',
+                vertical: '│',
+                joins: ['┬', '┴'],
+            },
+            Self::Rounded => Border {
+                corners: ['╭', '╮', '╰', '╯'],
+                horizontal: '─',
+                vertical: '│',
+                joins: ['┬', '┴'],
+            },
+            Self::Ascii => Border {
+                corners: ['+'; 4],
+                horizontal: '-',
+                vertical: '|',
+                joins: ['+'; 2],
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Table<T: fmt::Display, U: fmt::Display> {
     pub rows: Vec<(T, U)>,
+    style: TableStyle,
 }
 
 impl<T: fmt::Display, U: fmt::Display> Table<T, U> {
-    pub const fn new(rows: Vec<(T, U)>) -> Self {
-        Self { rows }
+    pub fn new(rows: Vec<(T, U)>) -> Self {
+        Self {
+            rows,
+            style: TableStyle::default(),
+        }
+    }
+
+    pub const fn with_style(mut self, style: TableStyle) -> Self {
+        self.style = style;
+        self
     }
 
     fn extend<I>(&mut self, iter: I)
@@ -44,7 +100,7 @@ where
 
 impl<T: fmt::Display, U: fmt::Display> From<(T, U)> for Table<T, U> {
     fn from(value: (T, U)) -> Self {
-        Self { rows: vec![value] }
+        Self::new(vec![value])
     }
 }
 
@@ -63,14 +119,27 @@ where
         let key_width = keys.iter().map(String::len).max().unwrap_or(0);
         let val_width = values.iter().map(String::len).max().unwrap_or(0);
 
+        let Border {
+            corners: [top_left, top_right, bottom_left, bottom_right],
+            horizontal: h,
+            vertical: v,
+            joins: [top_join, bottom_join],
+        } = self.style.border();
+
         let formatted_rows = self
             .rows
             .iter()
-            .map(|(key, value)| format!("│ {key:<key_width$} │ {value:<val_width$} │"))
+            .map(|(key, value)| format!("{v} {key:<key_width$} {v} {value:<val_width$} {v}"))
             .join("\n");
 
-        let top_border = format!("┌─{}─┬─{}─┐", "─".repeat(key_width), "─".repeat(val_width),);
-        let bottom_border = format!("└─{}─┴─{}─┘", "─".repeat(key_width), "─".repeat(val_width),);
+        let (keys_line, values_line) = (
+            h.to_string().repeat(key_width),
+            h.to_string().repeat(val_width),
+        );
+        let top_border =
+            format!("{top_left}{h}{keys_line}{h}{top_join}{h}{values_line}{h}{top_right}");
+        let bottom_border =
+            format!("{bottom_left}{h}{keys_line}{h}{bottom_join}{h}{values_line}{h}{bottom_right}");
 
         write!(f, "{}\n{}\n{}", top_border, formatted_rows, bottom_border,)
     }